@@ -0,0 +1,14 @@
+struct Particle {
+    vec2 position;
+    vec2 velocity;
+};
+
+// A body or a cluster of bodies in the tree. The n - 1 internal nodes of the binary radix tree
+// come first, leaf k (the k-th body in Morton order) is node n - 1 + k, so node 0 is the root for
+// any n. Quadtree nodes keep the index of the binary node they were collapsed from.
+struct Node {
+    // Center of mass in xy, total mass in z.
+    vec4 mass;
+    // Bounding box of the bodies, min in xy and max in zw.
+    vec4 bounds;
+};
\ No newline at end of file
@@ -0,0 +1,43 @@
+#version 450
+
+layout(local_size_x = 1024, local_size_y = 1, local_size_z = 1) in;
+
+#include "barnes_hut_common.glsl"
+
+layout(set = 0, binding = 0) readonly buffer Data {
+    Particle data[];
+} vertices;
+
+// Per workgroup, min in xy and max in zw.
+layout(set = 0, binding = 1) writeonly buffer Partials {
+    vec4 partials[];
+};
+
+shared vec4 reduced[1024];
+
+void main() {
+    uint idx = gl_GlobalInvocationID.x;
+    uint local_idx = gl_LocalInvocationID.x;
+
+    if (idx < vertices.data.length()) {
+        vec2 position = vertices.data[idx].position;
+        reduced[local_idx] = vec4(position, position);
+    } else {
+        reduced[local_idx] = vec4(vec2(3.4e38), vec2(-3.4e38));
+    }
+    memoryBarrierShared();
+    barrier();
+
+    for (uint stride = 512; stride > 0; stride /= 2) {
+        if (local_idx < stride) {
+            vec4 other = reduced[local_idx + stride];
+            reduced[local_idx] = vec4(min(reduced[local_idx].xy, other.xy), max(reduced[local_idx].zw, other.zw));
+        }
+        memoryBarrierShared();
+        barrier();
+    }
+
+    if (local_idx == 0) {
+        partials[gl_WorkGroupID.x] = reduced[0];
+    }
+}
\ No newline at end of file
@@ -0,0 +1,41 @@
+#version 450
+
+layout(local_size_x = 1024, local_size_y = 1, local_size_z = 1) in;
+
+// Min in xy and max in zw, per workgroup of the previous pass.
+layout(set = 0, binding = 0) readonly buffer Partials {
+    vec4 partials[];
+};
+
+// The same per workgroup of this pass, a single box after the last one.
+layout(set = 0, binding = 1) writeonly buffer Reduced {
+    vec4 reduced_partials[];
+};
+
+shared vec4 reduced[1024];
+
+void main() {
+    uint idx = gl_GlobalInvocationID.x;
+    uint local_idx = gl_LocalInvocationID.x;
+
+    if (idx < partials.length()) {
+        reduced[local_idx] = partials[idx];
+    } else {
+        reduced[local_idx] = vec4(vec2(3.4e38), vec2(-3.4e38));
+    }
+    memoryBarrierShared();
+    barrier();
+
+    for (uint stride = 512; stride > 0; stride /= 2) {
+        if (local_idx < stride) {
+            vec4 other = reduced[local_idx + stride];
+            reduced[local_idx] = vec4(min(reduced[local_idx].xy, other.xy), max(reduced[local_idx].zw, other.zw));
+        }
+        memoryBarrierShared();
+        barrier();
+    }
+
+    if (local_idx == 0) {
+        reduced_partials[gl_WorkGroupID.x] = reduced[0];
+    }
+}
@@ -0,0 +1,86 @@
+#version 450
+
+layout(local_size_x = 256, local_size_y = 1, local_size_z = 1) in;
+
+#include "barnes_hut_common.glsl"
+
+// Levels of internal quadtree nodes. The 30 bit Morton codes give 15, below them equal codes are
+// split by their index (see bh_hierarchy.comp.glsl), which gives at most 16 more.
+const int MAX_DEPTH = 31;
+
+// Opening a node pops it and pushes at most four children, so every level on the way down leaves
+// at most three nodes waiting, and the traversal never needs more.
+const int STACK_SIZE = 3 * MAX_DEPTH + 1;
+
+layout(set = 0, binding = 0) buffer Data {
+    Particle data[];
+} vertices;
+
+layout(set = 0, binding = 1) readonly buffer Values {
+    uint values[];
+};
+
+layout(set = 0, binding = 2) readonly buffer QuadChildren {
+    ivec4 quad_children[];
+};
+
+layout(set = 0, binding = 3) readonly buffer Nodes {
+    Node nodes[];
+};
+
+layout(push_constant) uniform Params {
+    float delta_time;
+    float gravity;
+    // Plummer softening length, keeps close encounters finite.
+    float softening;
+    // Opening angle, nodes smaller than this times their distance count as one body.
+    float theta;
+} params;
+
+// Like particle_nbody.comp.glsl, only updates velocities. Invocations go through the bodies in
+// Morton order, so neighbouring invocations walk similar parts of the quadtree.
+void main() {
+    uint k = gl_GlobalInvocationID.x;
+    uint count = values.length();
+    if (k >= count) {
+        return;
+    }
+
+    uint body = values[k];
+    vec2 position = vertices.data[body].position;
+    int internal_count = int(count) - 1;
+    int leaf = internal_count + int(k);
+    float softening2 = params.softening * params.softening;
+    float theta2 = params.theta * params.theta;
+
+    vec2 acceleration = vec2(0.0);
+    int stack[STACK_SIZE];
+    int top = 0;
+    stack[top++] = 0;
+    while (top > 0) {
+        int node = stack[--top];
+        if (node == leaf) {
+            continue;
+        }
+
+        Node cluster = nodes[node];
+        vec2 offset = cluster.mass.xy - position;
+        float distance2 = dot(offset, offset);
+        vec2 size = cluster.bounds.zw - cluster.bounds.xy;
+        float extent = max(size.x, size.y);
+
+        if (node >= internal_count || extent * extent < theta2 * distance2) {
+            float r2 = distance2 + softening2;
+            acceleration += offset * cluster.mass.z * inversesqrt(r2 * r2 * r2);
+        } else {
+            ivec4 quad = quad_children[node];
+            for (int quadrant = 0; quadrant < 4; quadrant++) {
+                if (quad[quadrant] >= 0) {
+                    stack[top++] = quad[quadrant];
+                }
+            }
+        }
+    }
+
+    vertices.data[body].velocity += acceleration * params.gravity * params.delta_time;
+}
\ No newline at end of file
@@ -0,0 +1,89 @@
+#version 450
+
+layout(local_size_x = 1024, local_size_y = 1, local_size_z = 1) in;
+
+// The sorted Morton codes.
+layout(set = 0, binding = 0) readonly buffer Keys {
+    uint keys[];
+};
+
+// Left and right child of every internal node.
+layout(set = 0, binding = 1) writeonly buffer Children {
+    ivec2 children[];
+};
+
+// Parent of every node but the root.
+layout(set = 0, binding = 2) writeonly buffer Parents {
+    int parents[];
+};
+
+// How many children of every internal node the summarize pass finished.
+layout(set = 0, binding = 3) writeonly buffer Visits {
+    uint visits[];
+};
+
+// Length of the prefix all keys under every internal node share, see delta.
+layout(set = 0, binding = 4) writeonly buffer Prefixes {
+    int prefixes[];
+};
+
+// Length of the common prefix of keys i and j, -1 if j is out of range. Equal keys are told apart
+// by their indices, so all keys are distinct.
+int delta(int i, int j) {
+    if (j < 0 || j >= keys.length()) {
+        return -1;
+    }
+    uint difference = keys[i] ^ keys[j];
+    if (difference == 0) {
+        return 32 + 31 - findMSB(uint(i ^ j));
+    }
+    return 31 - findMSB(difference);
+}
+
+// Builds internal node i of the radix tree over the sorted keys, independently of all other
+// nodes (Karras, "Maximizing Parallelism in the Construction of BVHs, Octrees, and k-d Trees").
+void main() {
+    int i = int(gl_GlobalInvocationID.x);
+    int internal_count = keys.length() - 1;
+    if (i >= internal_count) {
+        return;
+    }
+
+    // The node covers keys from i towards d, up to the first key sharing less with key i than
+    // its neighbour on the other side does.
+    int d = delta(i, i + 1) > delta(i, i - 1) ? 1 : -1;
+    int delta_min = delta(i, i - d);
+
+    int length_max = 2;
+    while (delta(i, i + length_max * d) > delta_min) {
+        length_max *= 2;
+    }
+    int length = 0;
+    for (int step = length_max / 2; step >= 1; step /= 2) {
+        if (delta(i, i + (length + step) * d) > delta_min) {
+            length += step;
+        }
+    }
+    int j = i + length * d;
+
+    // Split where the common prefix of the range ends.
+    int delta_node = delta(i, j);
+    int split = 0;
+    int step = length;
+    do {
+        step = (step + 1) / 2;
+        if (delta(i, i + (split + step) * d) > delta_node) {
+            split += step;
+        }
+    } while (step > 1);
+    int gamma = i + split * d + min(d, 0);
+
+    int left = min(i, j) == gamma ? internal_count + gamma : gamma;
+    int right = max(i, j) == gamma + 1 ? internal_count + gamma + 1 : gamma + 1;
+
+    children[i] = ivec2(left, right);
+    parents[left] = i;
+    parents[right] = i;
+    visits[i] = 0;
+    prefixes[i] = delta_node;
+}
\ No newline at end of file
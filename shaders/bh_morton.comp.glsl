@@ -0,0 +1,46 @@
+#version 450
+
+layout(local_size_x = 1024, local_size_y = 1, local_size_z = 1) in;
+
+#include "barnes_hut_common.glsl"
+
+layout(set = 0, binding = 0) readonly buffer Data {
+    Particle data[];
+} vertices;
+
+layout(set = 0, binding = 1) readonly buffer Bounds {
+    vec4 bounds;
+};
+
+layout(set = 0, binding = 2) writeonly buffer Keys {
+    uint keys[];
+};
+
+layout(set = 0, binding = 3) writeonly buffer Values {
+    uint values[];
+};
+
+// Spreads the low 15 bits of x to the even bits.
+uint spread_bits(uint x) {
+    x &= 0x7fff;
+    x = (x | (x << 8)) & 0x00ff00ff;
+    x = (x | (x << 4)) & 0x0f0f0f0f;
+    x = (x | (x << 2)) & 0x33333333;
+    x = (x | (x << 1)) & 0x55555555;
+    return x;
+}
+
+void main() {
+    uint idx = gl_GlobalInvocationID.x;
+    if (idx >= vertices.data.length()) {
+        return;
+    }
+
+    vec2 extent = max(bounds.zw - bounds.xy, vec2(1e-20));
+    vec2 normalized = clamp((vertices.data[idx].position - bounds.xy) / extent, 0.0, 1.0);
+    uvec2 cell = uvec2(normalized * 32767.0);
+
+    // 30 bits, x in the even and y in the odd ones.
+    keys[idx] = spread_bits(cell.x) | (spread_bits(cell.y) << 1);
+    values[idx] = idx;
+}
\ No newline at end of file
@@ -0,0 +1,47 @@
+#version 450
+
+layout(local_size_x = 1024, local_size_y = 1, local_size_z = 1) in;
+
+layout(set = 0, binding = 0) readonly buffer Children {
+    ivec2 children[];
+};
+
+layout(set = 0, binding = 1) readonly buffer Prefixes {
+    int prefixes[];
+};
+
+// Up to four children of every quadtree node, -1 for the missing ones.
+layout(set = 0, binding = 2) writeonly buffer QuadChildren {
+    ivec4 quad_children[];
+};
+
+// Collapses the binary radix tree into a quadtree. The keys interleave x and y, so every two bits
+// of prefix are one level of the quadtree, and a binary node whose prefix ends in the same level
+// as its parent's is merged into the parent. What is left are the quadtree cells that hold bodies
+// of more than one of their quadrants, with each quadrant's subtree as a child. Those are the root
+// and the nodes the children of kept nodes point to, the merged ones are never reached.
+void main() {
+    int i = int(gl_GlobalInvocationID.x);
+    int internal_count = prefixes.length();
+    if (i >= internal_count) {
+        return;
+    }
+
+    int level = prefixes[i] / 2;
+    ivec2 pair = children[i];
+    ivec4 quad = ivec4(-1);
+    int filled = 0;
+    for (int side = 0; side < 2; side++) {
+        int child = pair[side];
+        // A merged child ends its level, so its own children are one level deeper and kept.
+        if (child < internal_count && prefixes[child] / 2 == level) {
+            ivec2 grandchildren = children[child];
+            quad[filled++] = grandchildren.x;
+            quad[filled++] = grandchildren.y;
+        } else {
+            quad[filled++] = child;
+        }
+    }
+
+    quad_children[i] = quad;
+}
@@ -0,0 +1,43 @@
+#version 450
+
+// Keys per workgroup, one per invocation.
+layout(local_size_x = 256, local_size_y = 1, local_size_z = 1) in;
+
+const uint RADIX = 16;
+
+layout(set = 0, binding = 0) readonly buffer Keys {
+    uint keys[];
+};
+
+// Digit major, counts[digit * workgroups + workgroup], so the exclusive scan of the whole array
+// is where each workgroup's keys of each digit go.
+layout(set = 0, binding = 1) writeonly buffer Counts {
+    uint counts[];
+};
+
+layout(push_constant) uniform Params {
+    uint shift;
+} params;
+
+shared uint digit_counts[RADIX];
+
+void main() {
+    uint idx = gl_GlobalInvocationID.x;
+    uint local_idx = gl_LocalInvocationID.x;
+
+    if (local_idx < RADIX) {
+        digit_counts[local_idx] = 0;
+    }
+    memoryBarrierShared();
+    barrier();
+
+    if (idx < keys.length()) {
+        atomicAdd(digit_counts[(keys[idx] >> params.shift) & (RADIX - 1)], 1u);
+    }
+    memoryBarrierShared();
+    barrier();
+
+    if (local_idx < RADIX) {
+        counts[local_idx * gl_NumWorkGroups.x + gl_WorkGroupID.x] = digit_counts[local_idx];
+    }
+}
\ No newline at end of file
@@ -0,0 +1,45 @@
+#version 450
+
+// Every workgroup scans one block of the values, one per invocation.
+layout(local_size_x = 1024, local_size_y = 1, local_size_z = 1) in;
+
+const uint BLOCK_SIZE = 1024;
+
+// Replaced by its exclusive prefix sum within each block.
+layout(set = 0, binding = 0) buffer Values {
+    uint values[];
+};
+
+// The total of every block, scanned as the values of the next level.
+layout(set = 0, binding = 1) writeonly buffer Sums {
+    uint sums[];
+};
+
+shared uint scanned[BLOCK_SIZE];
+
+void main() {
+    uint idx = gl_GlobalInvocationID.x;
+    uint local_idx = gl_LocalInvocationID.x;
+    bool active = idx < values.length();
+
+    uint value = active ? values[idx] : 0;
+    scanned[local_idx] = value;
+    memoryBarrierShared();
+    barrier();
+
+    // Inclusive scan of the block.
+    for (uint offset = 1; offset < BLOCK_SIZE; offset *= 2) {
+        uint earlier = local_idx >= offset ? scanned[local_idx - offset] : 0;
+        barrier();
+        scanned[local_idx] += earlier;
+        memoryBarrierShared();
+        barrier();
+    }
+
+    if (active) {
+        values[idx] = scanned[local_idx] - value;
+    }
+    if (local_idx == BLOCK_SIZE - 1) {
+        sums[gl_WorkGroupID.x] = scanned[local_idx];
+    }
+}
@@ -0,0 +1,21 @@
+#version 450
+
+// Has to match the blocks of the scan pass.
+layout(local_size_x = 1024, local_size_y = 1, local_size_z = 1) in;
+
+// Scanned within each block, completed to the scan of all values.
+layout(set = 0, binding = 0) buffer Values {
+    uint values[];
+};
+
+// The exclusive scan of the block totals, where every block starts.
+layout(set = 0, binding = 1) readonly buffer Sums {
+    uint sums[];
+};
+
+void main() {
+    uint idx = gl_GlobalInvocationID.x;
+    if (idx < values.length()) {
+        values[idx] += sums[gl_WorkGroupID.x];
+    }
+}
@@ -0,0 +1,61 @@
+#version 450
+
+// Has to match the workgroups of the histogram pass.
+layout(local_size_x = 256, local_size_y = 1, local_size_z = 1) in;
+
+const uint RADIX = 16;
+const uint WORKGROUP_SIZE = 256;
+
+layout(set = 0, binding = 0) readonly buffer KeysIn {
+    uint keys_in[];
+};
+
+layout(set = 0, binding = 1) readonly buffer ValuesIn {
+    uint values_in[];
+};
+
+// The scanned histogram.
+layout(set = 0, binding = 2) readonly buffer Offsets {
+    uint offsets[];
+};
+
+layout(set = 0, binding = 3) writeonly buffer KeysOut {
+    uint keys_out[];
+};
+
+layout(set = 0, binding = 4) writeonly buffer ValuesOut {
+    uint values_out[];
+};
+
+layout(push_constant) uniform Params {
+    uint shift;
+} params;
+
+// RADIX marks invocations past the end.
+shared uint digits[WORKGROUP_SIZE];
+
+void main() {
+    uint idx = gl_GlobalInvocationID.x;
+    uint local_idx = gl_LocalInvocationID.x;
+    bool active = idx < keys_in.length();
+
+    uint key = active ? keys_in[idx] : 0;
+    uint digit = active ? (key >> params.shift) & (RADIX - 1) : RADIX;
+    digits[local_idx] = digit;
+    memoryBarrierShared();
+    barrier();
+
+    if (!active) {
+        return;
+    }
+
+    // Keys of the same digit keep their order, which is what makes the passes add up to a sort.
+    uint rank = 0;
+    for (uint i = 0; i < local_idx; i++) {
+        rank += digits[i] == digit ? 1u : 0u;
+    }
+
+    uint destination = offsets[digit * gl_NumWorkGroups.x + gl_WorkGroupID.x] + rank;
+    keys_out[destination] = key;
+    values_out[destination] = values_in[idx];
+}
\ No newline at end of file
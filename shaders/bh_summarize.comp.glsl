@@ -0,0 +1,69 @@
+#version 450
+
+layout(local_size_x = 1024, local_size_y = 1, local_size_z = 1) in;
+
+#include "barnes_hut_common.glsl"
+
+layout(set = 0, binding = 0) readonly buffer Data {
+    Particle data[];
+} vertices;
+
+// Body index of every sorted key.
+layout(set = 0, binding = 1) readonly buffer Values {
+    uint values[];
+};
+
+layout(set = 0, binding = 2) readonly buffer Children {
+    ivec2 children[];
+};
+
+layout(set = 0, binding = 3) readonly buffer Parents {
+    int parents[];
+};
+
+layout(set = 0, binding = 4) coherent buffer Visits {
+    uint visits[];
+};
+
+layout(set = 0, binding = 5) coherent buffer Nodes {
+    Node nodes[];
+};
+
+layout(push_constant) uniform Params {
+    float body_mass;
+} params;
+
+// Every invocation writes one leaf and walks towards the root. Of the two invocations reaching a
+// node, the first one stops and the second one combines both children, so every node is written
+// exactly once and only after both of its children.
+void main() {
+    uint k = gl_GlobalInvocationID.x;
+    uint count = values.length();
+    if (k >= count) {
+        return;
+    }
+
+    int node = int(count - 1 + k);
+    vec2 position = vertices.data[values[k]].position;
+    nodes[node] = Node(vec4(position, params.body_mass, 0.0), vec4(position, position));
+    memoryBarrierBuffer();
+
+    while (node != 0) {
+        node = parents[node];
+        if (atomicAdd(visits[node], 1u) == 0) {
+            return;
+        }
+        memoryBarrierBuffer();
+
+        ivec2 pair = children[node];
+        Node left = nodes[pair.x];
+        Node right = nodes[pair.y];
+        float mass = left.mass.z + right.mass.z;
+        vec2 center = (left.mass.xy * left.mass.z + right.mass.xy * right.mass.z) / mass;
+        nodes[node] = Node(
+            vec4(center, mass, 0.0),
+            vec4(min(left.bounds.xy, right.bounds.xy), max(left.bounds.zw, right.bounds.zw))
+        );
+        memoryBarrierBuffer();
+    }
+}
\ No newline at end of file
@@ -0,0 +1,44 @@
+#version 450
+
+layout(local_size_x = 8, local_size_y = 8, local_size_z = 1) in;
+
+layout(set = 0, binding = 0) uniform sampler2D src;
+layout(set = 0, binding = 1, rgba16f) uniform writeonly image2D dst;
+
+layout(push_constant) uniform PushConstants {
+    float kernel[9];
+    uint sobel;
+} pc;
+
+const vec3 LUMINANCE = vec3(0.2126, 0.7152, 0.0722);
+
+// 3x3 convolution of the color, or the Sobel gradient magnitude of the luminance.
+void main() {
+    ivec2 size = imageSize(dst);
+    ivec2 pixel = ivec2(gl_GlobalInvocationID.xy);
+    if (pixel.x >= size.x || pixel.y >= size.y) {
+        return;
+    }
+
+    vec4 center = texelFetch(src, pixel, 0);
+    vec4 sum = vec4(0.0);
+    vec2 gradient = vec2(0.0);
+    for (int dy = -1; dy <= 1; dy++) {
+        for (int dx = -1; dx <= 1; dx++) {
+            ivec2 position = clamp(pixel + ivec2(dx, dy), ivec2(0), size - ivec2(1));
+            vec4 texel = texelFetch(src, position, 0);
+
+            sum += texel * pc.kernel[(dy + 1) * 3 + dx + 1];
+
+            float weight = (dx == 0 || dy == 0) ? 2.0 : 1.0;
+            float luminance = dot(texel.rgb, LUMINANCE);
+            gradient += vec2(dx, dy) * weight * luminance;
+        }
+    }
+
+    if (pc.sobel != 0) {
+        imageStore(dst, pixel, vec4(vec3(length(gradient)), center.a));
+    } else {
+        imageStore(dst, pixel, vec4(sum.rgb, center.a));
+    }
+}
@@ -0,0 +1,33 @@
+#version 450
+
+layout(local_size_x = 8, local_size_y = 8, local_size_z = 1) in;
+
+layout(set = 0, binding = 0) uniform sampler2D src;
+layout(set = 0, binding = 1, rgba16f) uniform writeonly image2D dst;
+
+layout(push_constant) uniform PushConstants {
+    mat4 matrix;
+    vec4 offset;
+    float threshold;
+    uint apply_threshold;
+} pc;
+
+const vec3 LUMINANCE = vec3(0.2126, 0.7152, 0.0722);
+
+// Either `matrix * color + offset`, or black/white depending on the luminance.
+void main() {
+    ivec2 size = imageSize(dst);
+    ivec2 pixel = ivec2(gl_GlobalInvocationID.xy);
+    if (pixel.x >= size.x || pixel.y >= size.y) {
+        return;
+    }
+
+    vec4 color = texelFetch(src, pixel, 0);
+
+    if (pc.apply_threshold != 0) {
+        float value = dot(color.rgb, LUMINANCE) > pc.threshold ? 1.0 : 0.0;
+        imageStore(dst, pixel, vec4(vec3(value), color.a));
+    } else {
+        imageStore(dst, pixel, pc.matrix * color + pc.offset);
+    }
+}
@@ -0,0 +1,32 @@
+#version 450
+
+layout(local_size_x = 8, local_size_y = 8, local_size_z = 1) in;
+
+layout(set = 0, binding = 0) uniform sampler2D src;
+layout(set = 0, binding = 1, rgba16f) uniform writeonly image2D dst;
+
+layout(set = 0, binding = 2) buffer Weights {
+    float data[];
+} weights;
+
+layout(push_constant) uniform PushConstants {
+    ivec2 direction;
+    int radius;
+} pc;
+
+// One dimension of a separable convolution, clamping samples to the image edge.
+void main() {
+    ivec2 size = imageSize(dst);
+    ivec2 pixel = ivec2(gl_GlobalInvocationID.xy);
+    if (pixel.x >= size.x || pixel.y >= size.y) {
+        return;
+    }
+
+    vec4 sum = vec4(0.0);
+    for (int i = -pc.radius; i <= pc.radius; i++) {
+        ivec2 position = clamp(pixel + pc.direction * i, ivec2(0), size - ivec2(1));
+        sum += texelFetch(src, position, 0) * weights.data[i + pc.radius];
+    }
+
+    imageStore(dst, pixel, sum);
+}
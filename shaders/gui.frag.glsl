@@ -0,0 +1,13 @@
+#version 450
+
+layout(location = 0) in vec2 v_tex_coord;
+layout(location = 1) in vec4 v_color;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D font;
+
+void main() {
+    // The font texture only holds coverage, the color is premultiplied by it.
+    f_color = v_color * texture(font, v_tex_coord).r;
+}
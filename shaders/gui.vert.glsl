@@ -0,0 +1,26 @@
+#version 450
+
+layout(location = 0) in vec2 position;
+layout(location = 1) in vec2 tex_coord;
+layout(location = 2) in vec4 color;
+
+layout(location = 0) out vec2 v_tex_coord;
+layout(location = 1) out vec4 v_color;
+
+layout(push_constant) uniform Screen {
+    vec2 size;
+} screen;
+
+vec3 srgb_to_linear(vec3 srgb) {
+    bvec3 cutoff = lessThan(srgb, vec3(0.04045));
+    vec3 lower = srgb / 12.92;
+    vec3 higher = pow((srgb + 0.055) / 1.055, vec3(2.4));
+    return mix(higher, lower, cutoff);
+}
+
+void main() {
+    gl_Position = vec4(position / screen.size * 2.0 - 1.0, 0.0, 1.0);
+    v_tex_coord = tex_coord;
+    // egui picks its colors in sRGB, the swapchain expects linear values.
+    v_color = vec4(srgb_to_linear(color.rgb), color.a);
+}
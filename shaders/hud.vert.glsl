@@ -0,0 +1,19 @@
+#version 450
+
+layout(location = 0) in vec2 position;
+layout(location = 1) in vec2 tex_coord;
+layout(location = 2) in vec4 color;
+
+layout(location = 0) out vec2 v_tex_coord;
+layout(location = 1) out vec4 v_color;
+
+layout(push_constant) uniform Screen {
+    vec2 size;
+} screen;
+
+void main() {
+    // Pixels from the top left corner, which is (-1, -1) in Vulkan clip space.
+    gl_Position = vec4(position / screen.size * 2.0 - 1.0, 0.0, 1.0);
+    v_tex_coord = tex_coord;
+    v_color = color;
+}
@@ -0,0 +1,16 @@
+#version 450
+
+layout(location = 0) in vec3 v_color;
+layout(location = 1) in vec2 v_tex_coord;
+
+layout(location = 0) out vec4 f_color;
+
+void main() {
+    // Round the quads off using their texture coordinates.
+    float distance = length(v_tex_coord * 2.0 - 1.0);
+    if (distance > 1.0) {
+        discard;
+    }
+
+    f_color = vec4(v_color * (1.0 - 0.5 * distance), 1.0);
+}
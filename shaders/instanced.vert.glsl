@@ -0,0 +1,30 @@
+#version 450
+
+// Per vertex.
+layout(location = 0) in vec2 position;
+layout(location = 1) in vec3 color;
+layout(location = 2) in vec2 tex_coord;
+
+// Per instance.
+layout(location = 3) in vec2 offset;
+layout(location = 4) in float scale;
+layout(location = 5) in float angle;
+layout(location = 6) in vec3 tint;
+
+layout(location = 0) out vec3 v_color;
+layout(location = 1) out vec2 v_tex_coord;
+
+layout(push_constant) uniform View {
+    float angle;
+    float aspect;
+} view;
+
+void main() {
+    float turn = angle + view.angle;
+    mat2 rotation = mat2(cos(turn), sin(turn), -sin(turn), cos(turn));
+    vec2 turned = offset + rotation * position * scale;
+
+    v_color = color * tint;
+    v_tex_coord = tex_coord;
+    gl_Position = vec4(turned.x / view.aspect, turned.y, 0.0, 1.0);
+}
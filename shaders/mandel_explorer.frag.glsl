@@ -0,0 +1,41 @@
+#version 450
+
+layout(location = 0) out vec4 f_color;
+
+layout(push_constant) uniform Params {
+    vec4 inside_color;
+    vec4 near_color;
+    vec4 far_color;
+    vec2 center;
+    vec2 size;
+    // Complex units per pixel.
+    float scale;
+    uint max_iterations;
+} params;
+
+const float ESCAPE_RADIUS = 16.0;
+
+void main() {
+    vec2 offset = gl_FragCoord.xy - params.size * 0.5;
+    vec2 c = params.center + vec2(offset.x, -offset.y) * params.scale;
+
+    vec2 z = vec2(0.0);
+    uint i;
+    for (i = 0; i < params.max_iterations; i++) {
+        z = vec2(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y) + c;
+
+        if (dot(z, z) > ESCAPE_RADIUS * ESCAPE_RADIUS) {
+            break;
+        }
+    }
+
+    if (i == params.max_iterations) {
+        f_color = params.inside_color;
+        return;
+    }
+
+    // Continuous iteration count, so the bands do not show.
+    float smooth_i = float(i) + 1.0 - log2(log2(dot(z, z)) * 0.5);
+    float t = clamp(smooth_i / float(params.max_iterations), 0.0, 1.0);
+    f_color = mix(params.far_color, params.near_color, sqrt(t));
+}
\ No newline at end of file
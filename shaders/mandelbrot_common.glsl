@@ -0,0 +1,128 @@
+layout(push_constant) uniform PushConstants {
+    uint grid_size;
+    uint pattern;
+    uint filter_kind;
+    float threshold;
+} pc;
+
+const uint PATTERN_GRID = 0;
+const uint PATTERN_JITTERED = 1;
+const uint PATTERN_ROTATED_GRID = 2;
+
+const uint FILTER_BOX = 0;
+const uint FILTER_LANCZOS = 1;
+
+const float ITERATION_STEP = 0.005;
+
+const float LANCZOS_RADIUS = 2.0;
+const float PI = 3.14159265358979;
+
+float mandel(vec2 pixel, vec2 size) {
+    vec2 norm_coords = pixel / size;
+    vec2 c = (norm_coords - vec2(0.5)) * 2.0 - vec2(1.0, 0.0) * vec2(0.5);
+
+    vec2 z = vec2(0.0, 0.0);
+    float i;
+    for (i = 0.0; i < 1.0; i += ITERATION_STEP) {
+        z = vec2(
+            z.x * z.x - z.y * z.y + c.x,
+            z.y * z.x + z.x * z.y + c.y
+        );
+
+        if (length(z) > 4.0) {
+            break;
+        }
+    }
+
+    return i;
+}
+
+uint hash(uint x) {
+    x ^= x >> 16;
+    x *= 0x7feb352dU;
+    x ^= x >> 15;
+    x *= 0x846ca68bU;
+    x ^= x >> 16;
+    return x;
+}
+
+// Offset of sample (sx, sy) inside its pixel, in [-0.5, 0.5).
+vec2 sample_offset(uint sx, uint sy, uvec2 pixel) {
+    float n = float(pc.grid_size);
+    vec2 cell = (vec2(sx, sy) + vec2(0.5)) / n - vec2(0.5);
+
+    if (pc.pattern == PATTERN_JITTERED) {
+        uint seed = hash(pixel.x ^ hash(pixel.y ^ hash(sy * pc.grid_size + sx)));
+        vec2 jitter = vec2(hash(seed), hash(seed ^ 0x9e3779b9U)) / 4294967295.0;
+        return cell + (jitter - vec2(0.5)) / n;
+    }
+
+    if (pc.pattern == PATTERN_ROTATED_GRID) {
+        // atan(1/2) keeps every sample on its own row and column.
+        float angle = 0.4636476;
+        mat2 rotation = mat2(cos(angle), sin(angle), -sin(angle), cos(angle));
+        return fract(rotation * cell + vec2(0.5)) - vec2(0.5);
+    }
+
+    return cell;
+}
+
+float lanczos(float x) {
+    if (abs(x) < 1e-5) {
+        return 1.0;
+    }
+    if (abs(x) >= LANCZOS_RADIUS) {
+        return 0.0;
+    }
+    float px = PI * x;
+    return LANCZOS_RADIUS * sin(px) * sin(px / LANCZOS_RADIUS) / (px * px);
+}
+
+// Filters grid_size^2 samples per pixel over the footprint of the selected filter.
+float supersample(uvec2 pixel, vec2 size) {
+    int radius = pc.filter_kind == FILTER_LANCZOS ? int(LANCZOS_RADIUS) : 0;
+    vec2 center = vec2(pixel) + vec2(0.5);
+
+    float sum = 0.0;
+    float weight_sum = 0.0;
+    for (int cy = -radius; cy <= radius; cy++) {
+        for (int cx = -radius; cx <= radius; cx++) {
+            uvec2 cell = uvec2(ivec2(pixel) + ivec2(cx, cy));
+            for (uint sy = 0; sy < pc.grid_size; sy++) {
+                for (uint sx = 0; sx < pc.grid_size; sx++) {
+                    vec2 position = vec2(cx, cy) + center + sample_offset(sx, sy, cell);
+                    vec2 d = position - center;
+
+                    float weight = 1.0;
+                    if (pc.filter_kind == FILTER_LANCZOS) {
+                        weight = lanczos(d.x) * lanczos(d.y);
+                    }
+
+                    sum += mandel(position, size) * weight;
+                    weight_sum += weight;
+                }
+            }
+        }
+    }
+
+    return clamp(sum / weight_sum, 0.0, 1.0);
+}
+
+#ifdef RAW_ITERATIONS
+// Raw outputs store the (filtered) iteration count instead of a grey value.
+vec4 to_output(float i) {
+    return vec4(i / ITERATION_STEP);
+}
+
+float from_output(vec4 value) {
+    return value.r * ITERATION_STEP;
+}
+#else
+vec4 to_output(float i) {
+    return vec4(vec3(i), 1.0);
+}
+
+float from_output(vec4 value) {
+    return value.r;
+}
+#endif
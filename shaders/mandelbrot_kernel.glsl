@@ -0,0 +1,11 @@
+layout(local_size_x = 8, local_size_y = 8, local_size_z = 1) in;
+
+layout (set = 0, binding = 0, OUTPUT_FORMAT) uniform writeonly image2D img;
+
+#include "mandelbrot_common.glsl"
+
+void main() {
+    float i = supersample(gl_GlobalInvocationID.xy, vec2(imageSize(img)));
+
+    imageStore(img, ivec2(gl_GlobalInvocationID.xy), to_output(i));
+}
@@ -0,0 +1,6 @@
+#version 450
+
+#define OUTPUT_FORMAT r32f
+#define RAW_ITERATIONS
+
+#include "mandelbrot_kernel.glsl"
@@ -0,0 +1,5 @@
+#version 450
+
+#define OUTPUT_FORMAT rgba8
+
+#include "mandelbrot_refine_kernel.glsl"
@@ -0,0 +1,31 @@
+layout(local_size_x = 8, local_size_y = 8, local_size_z = 1) in;
+
+layout (set = 0, binding = 0, OUTPUT_FORMAT) uniform writeonly image2D img;
+layout (set = 0, binding = 1, OUTPUT_FORMAT) uniform readonly image2D coarse;
+
+#include "mandelbrot_common.glsl"
+
+// Re-renders only the pixels of `coarse` whose neighbourhood differs by more than `threshold`.
+void main() {
+    ivec2 pixel = ivec2(gl_GlobalInvocationID.xy);
+    ivec2 size = imageSize(coarse);
+    vec4 center = imageLoad(coarse, pixel);
+
+    float difference = 0.0;
+    for (int dy = -1; dy <= 1; dy++) {
+        for (int dx = -1; dx <= 1; dx++) {
+            ivec2 neighbour = clamp(pixel + ivec2(dx, dy), ivec2(0), size - ivec2(1));
+            difference = max(
+                difference,
+                abs(from_output(imageLoad(coarse, neighbour)) - from_output(center))
+            );
+        }
+    }
+
+    vec4 to_write = center;
+    if (difference > pc.threshold) {
+        to_write = to_output(supersample(gl_GlobalInvocationID.xy, vec2(size)));
+    }
+
+    imageStore(img, pixel, to_write);
+}
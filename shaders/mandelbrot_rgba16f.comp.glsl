@@ -0,0 +1,5 @@
+#version 450
+
+#define OUTPUT_FORMAT rgba16f
+
+#include "mandelbrot_kernel.glsl"
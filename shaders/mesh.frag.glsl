@@ -0,0 +1,14 @@
+#version 450
+
+layout(location = 0) in vec3 v_normal;
+
+layout(location = 0) out vec4 f_color;
+
+const vec3 LIGHT_DIRECTION = normalize(vec3(-0.4, 0.8, 0.6));
+const vec3 BASE_COLOR = vec3(0.9, 0.6, 0.3);
+const float AMBIENT = 0.15;
+
+void main() {
+    float lambert = max(dot(normalize(v_normal), LIGHT_DIRECTION), 0.0);
+    f_color = vec4(BASE_COLOR * (AMBIENT + (1.0 - AMBIENT) * lambert), 1.0);
+}
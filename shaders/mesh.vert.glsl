@@ -0,0 +1,17 @@
+#version 450
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec3 normal;
+
+layout(location = 0) out vec3 v_normal;
+
+layout(set = 0, binding = 0) uniform Transforms {
+    mat4 model;
+    mat4 view;
+    mat4 projection;
+} transforms;
+
+void main() {
+    v_normal = transpose(inverse(mat3(transforms.model))) * normal;
+    gl_Position = transforms.projection * transforms.view * transforms.model * vec4(position, 1.0);
+}
@@ -0,0 +1,25 @@
+#version 450
+
+layout(local_size_x = 1024, local_size_y = 1, local_size_z = 1) in;
+
+struct Particle {
+    vec2 position;
+    vec2 velocity;
+};
+
+layout(set = 0, binding = 0) buffer Data {
+    Particle data[];
+} vertices;
+
+layout(push_constant) uniform Params {
+    float delta_time;
+} params;
+
+void main() {
+    uint idx = gl_GlobalInvocationID.x;
+    if (idx >= vertices.data.length()) {
+        return;
+    }
+
+    vertices.data[idx].position += vertices.data[idx].velocity * params.delta_time;
+}
\ No newline at end of file
@@ -0,0 +1,76 @@
+#version 450
+
+layout(local_size_x = 256, local_size_y = 1, local_size_z = 1) in;
+
+const uint TILE_SIZE = 256;
+
+struct Particle {
+    vec2 position;
+    vec2 velocity;
+};
+
+layout(set = 0, binding = 0) readonly buffer Data {
+    Particle data[];
+} vertices;
+
+// Per workgroup: kinetic energy, potential energy and momentum.
+layout(set = 1, binding = 0) writeonly buffer Sums {
+    vec4 sums[];
+};
+
+layout(push_constant) uniform Params {
+    float gravity;
+    float softening;
+    float body_mass;
+} params;
+
+shared vec2 tile[TILE_SIZE];
+shared vec4 partial[TILE_SIZE];
+
+void main() {
+    uint idx = gl_GlobalInvocationID.x;
+    uint local_idx = gl_LocalInvocationID.x;
+    uint count = vertices.data.length();
+
+    vec2 position = idx < count ? vertices.data[idx].position : vec2(0.0);
+    vec2 velocity = idx < count ? vertices.data[idx].velocity : vec2(0.0);
+    float softening2 = params.softening * params.softening;
+
+    // The softened potential, the one whose gradient the N-body pass integrates.
+    float potential = 0.0;
+    for (uint start = 0; start < count; start += TILE_SIZE) {
+        uint j = start + local_idx;
+        tile[local_idx] = j < count ? vertices.data[j].position : vec2(0.0);
+        memoryBarrierShared();
+        barrier();
+
+        uint tile_count = min(TILE_SIZE, count - start);
+        for (uint k = 0; k < tile_count; k++) {
+            if (start + k != idx) {
+                vec2 offset = tile[k] - position;
+                potential -= inversesqrt(dot(offset, offset) + softening2);
+            }
+        }
+        barrier();
+    }
+
+    float m = params.body_mass;
+    // Every pair shows up twice, once from each side, hence the halved potential.
+    partial[local_idx] = idx < count
+        ? vec4(0.5 * m * dot(velocity, velocity), 0.5 * params.gravity * m * m * potential, m * velocity)
+        : vec4(0.0);
+    memoryBarrierShared();
+    barrier();
+
+    for (uint stride = TILE_SIZE / 2; stride > 0; stride /= 2) {
+        if (local_idx < stride) {
+            partial[local_idx] += partial[local_idx + stride];
+        }
+        memoryBarrierShared();
+        barrier();
+    }
+
+    if (local_idx == 0) {
+        sums[gl_WorkGroupID.x] = partial[0];
+    }
+}
\ No newline at end of file
@@ -0,0 +1,118 @@
+#version 450
+
+layout(local_size_x = 1024, local_size_y = 1, local_size_z = 1) in;
+
+struct Particle {
+    vec2 position;
+    vec2 velocity;
+};
+
+layout(set = 0, binding = 0) buffer Data {
+    Particle data[];
+} vertices;
+
+layout(push_constant) uniform Params {
+    uint seed;
+    uint distribution;
+} params;
+
+// Same order as the Distribution enum.
+const uint DISTRIBUTION_SQUARE = 0;
+const uint DISTRIBUTION_DISC = 1;
+const uint DISTRIBUTION_CLUSTERS = 2;
+const uint DISTRIBUTION_GALAXY = 3;
+const uint DISTRIBUTION_RING = 4;
+const uint DISTRIBUTION_SPIRAL = 5;
+const uint DISTRIBUTION_GRID = 6;
+
+const float PI = 3.14159265358979;
+
+const uint CLUSTER_COUNT = 5;
+const float CLUSTER_SIGMA = 0.08;
+const uint ARM_COUNT = 3;
+// Radians an arm turns from the center to the rim.
+const float ARM_WINDING = 4.0;
+// Speed of a circular orbit at radius 1 around the center.
+const float ORBIT_SPEED = 0.3;
+
+uint hash(uint x) {
+    x ^= x >> 16;
+    x *= 0x7feb352dU;
+    x ^= x >> 15;
+    x *= 0x846ca68bU;
+    x ^= x >> 16;
+    return x;
+}
+
+// The top 24 bits as a float in [0, 1), exact in single precision.
+float to_unit(uint x) {
+    return float(x >> 8) * (1.0 / 16777216.0);
+}
+
+float next_unit(inout uint state) {
+    state = hash(state);
+    return to_unit(state);
+}
+
+// Two independent standard normal samples (Box-Muller).
+vec2 next_gaussian(inout uint state) {
+    float radius = sqrt(-2.0 * log(1.0 - next_unit(state)));
+    float angle = 2.0 * PI * next_unit(state);
+    return radius * vec2(cos(angle), sin(angle));
+}
+
+vec2 polar(float radius, float angle) {
+    return radius * vec2(cos(angle), sin(angle));
+}
+
+// Velocity of a circular orbit around the center, counter-clockwise on screen.
+vec2 orbit(vec2 position) {
+    float radius = max(length(position), 0.05);
+    vec2 tangent = vec2(position.y, -position.x) / radius;
+    return tangent * ORBIT_SPEED / sqrt(radius);
+}
+
+void main() {
+    uint idx = gl_GlobalInvocationID.x;
+    uint count = vertices.data.length();
+    if (idx >= count) {
+        return;
+    }
+
+    uint state = idx ^ hash(params.seed);
+    vec2 position;
+    vec2 velocity = vec2(0.0);
+
+    if (params.distribution == DISTRIBUTION_DISC) {
+        position = polar(sqrt(next_unit(state)), 2.0 * PI * next_unit(state));
+    } else if (params.distribution == DISTRIBUTION_CLUSTERS) {
+        // Cluster centers depend on the seed only, so every particle agrees on them.
+        uint cluster = hash(params.seed ^ (idx % CLUSTER_COUNT + 1) * 0x9e3779b9U);
+        vec2 center = vec2(to_unit(cluster), to_unit(hash(cluster))) * 1.4 - 0.7;
+        position = center + next_gaussian(state) * CLUSTER_SIGMA;
+    } else if (params.distribution == DISTRIBUTION_GALAXY) {
+        // Denser towards the center, cut off at the rim.
+        float radius = min(-0.3 * log(1.0 - next_unit(state)), 1.0);
+        position = polar(radius, 2.0 * PI * next_unit(state));
+        velocity = orbit(position);
+    } else if (params.distribution == DISTRIBUTION_RING) {
+        float radius = 0.6 + next_gaussian(state).x * 0.03;
+        position = polar(radius, 2.0 * PI * next_unit(state));
+    } else if (params.distribution == DISTRIBUTION_SPIRAL) {
+        float t = next_unit(state);
+        float radius = 0.05 + 0.85 * t;
+        float arm = float(idx % ARM_COUNT) * 2.0 * PI / float(ARM_COUNT);
+        float angle = arm + t * ARM_WINDING + next_gaussian(state).x * 0.15;
+        position = polar(radius, angle);
+        velocity = orbit(position);
+    } else if (params.distribution == DISTRIBUTION_GRID) {
+        uint side = uint(ceil(sqrt(float(count))));
+        vec2 cell = vec2(idx % side, idx / side) + 0.5;
+        position = cell / float(side) * 2.0 - 1.0;
+    } else {
+        position = vec2(next_unit(state), next_unit(state)) * 2.0 - 1.0;
+    }
+
+    vertices.data[idx].position = position;
+    vertices.data[idx].velocity = velocity;
+}
\ No newline at end of file
@@ -0,0 +1,56 @@
+#version 450
+
+// Bodies per tile, the positions of one tile are shared by the whole workgroup.
+layout(local_size_x = 256, local_size_y = 1, local_size_z = 1) in;
+
+const uint TILE_SIZE = 256;
+
+struct Particle {
+    vec2 position;
+    vec2 velocity;
+};
+
+layout(set = 0, binding = 0) buffer Data {
+    Particle data[];
+} vertices;
+
+layout(push_constant) uniform Params {
+    float delta_time;
+    float gravity;
+    // Plummer softening length, keeps close encounters finite.
+    float softening;
+    float body_mass;
+} params;
+
+shared vec2 tile[TILE_SIZE];
+
+// Only updates velocities, the positions every workgroup reads stay untouched until the drift
+// pass.
+void main() {
+    uint idx = gl_GlobalInvocationID.x;
+    uint count = vertices.data.length();
+
+    // Invocations past the end still load tiles and reach the barriers.
+    vec2 position = idx < count ? vertices.data[idx].position : vec2(0.0);
+    vec2 acceleration = vec2(0.0);
+    float softening2 = params.softening * params.softening;
+
+    for (uint start = 0; start < count; start += TILE_SIZE) {
+        uint j = start + gl_LocalInvocationID.x;
+        tile[gl_LocalInvocationID.x] = j < count ? vertices.data[j].position : vec2(0.0);
+        memoryBarrierShared();
+        barrier();
+
+        uint tile_count = min(TILE_SIZE, count - start);
+        for (uint k = 0; k < tile_count; k++) {
+            vec2 offset = tile[k] - position;
+            float r2 = dot(offset, offset) + softening2;
+            acceleration += offset * inversesqrt(r2 * r2 * r2);
+        }
+        barrier();
+    }
+
+    if (idx < count) {
+        vertices.data[idx].velocity += acceleration * params.gravity * params.body_mass * params.delta_time;
+    }
+}
\ No newline at end of file
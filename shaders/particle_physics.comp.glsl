@@ -7,27 +7,56 @@ struct Particle {
     vec2 velocity;
 };
 
+// Negative masses push particles away.
+struct Attractor {
+    vec2 position;
+    float mass;
+    // Distances below this count as this, so the pull stays finite at the center.
+    float radius;
+    // The pull falls off with the distance to this power.
+    float falloff;
+    float padding;
+};
+
 layout(set = 0, binding = 0) buffer Data {
     Particle data[];
 } vertices;
 
 layout(set = 1, binding = 0) uniform UniformBufferObject {
-    vec2 target;
     float delta_time;
-    float target_mass;
+    float damping;
+    uint attractor_count;
 } ubo;
 
+layout(set = 1, binding = 1) readonly buffer Attractors {
+    Attractor attractors[];
+};
+
 void main() {
     float particle_mass = 0.01;
 
     uint idx = gl_GlobalInvocationID.x;
+    if (idx >= vertices.data.length()) {
+        return;
+    }
+
+    vec2 position = vertices.data[idx].position;
+    vec2 delta = vec2(0.0);
 
-    float gravity = (ubo.target_mass * particle_mass) / distance(ubo.target, vertices.data[idx].position);
+    for (uint i = 0; i < ubo.attractor_count; i++) {
+        Attractor attractor = attractors[i];
+        vec2 offset = attractor.position - position;
+        float d = length(offset);
+        if (d == 0.0) {
+            continue;
+        }
 
-    vec2 delta = normalize(ubo.target - vertices.data[idx].position) * gravity;
+        float gravity = (attractor.mass * particle_mass) / pow(max(d, attractor.radius), attractor.falloff);
+        delta += offset / d * gravity;
+    }
 
     vertices.data[idx].velocity += delta;
-    vertices.data[idx].velocity *= 0.999 * (1-ubo.delta_time);
+    vertices.data[idx].velocity *= ubo.damping * (1-ubo.delta_time);
 
     vertices.data[idx].position += vertices.data[idx].velocity * ubo.delta_time;
-}
+}
\ No newline at end of file
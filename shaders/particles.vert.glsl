@@ -3,10 +3,15 @@
 layout(location = 0) in vec2 position;
 layout(location = 1) in vec2 velocity;
 
+layout(location = 0) out vec4 v_color;
+
 layout(binding = 0) uniform UniformBufferObject {
-    float aspect;
+    mat4 view;
+    vec4 slow_color;
+    vec4 fast_color;
 } ubo;
 
 void main() {
-    gl_Position = vec4(position.x * ubo.aspect, position.y, 0.0, 1.0);
+    gl_Position = ubo.view * vec4(position, 0.0, 1.0);
+    v_color = mix(ubo.slow_color, ubo.fast_color, clamp(length(velocity), 0.0, 1.0));
 }
\ No newline at end of file
@@ -0,0 +1,18 @@
+#version 450
+
+layout(location = 0) in vec3 v_normal;
+layout(location = 1) in vec2 v_tex_coord;
+layout(location = 2) flat in vec4 v_base_color;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 1, binding = 0) uniform sampler2D base_color_texture;
+
+const vec3 LIGHT_DIRECTION = normalize(vec3(-0.4, 0.8, 0.6));
+const float AMBIENT = 0.15;
+
+void main() {
+    vec4 base_color = v_base_color * texture(base_color_texture, v_tex_coord);
+    float lambert = max(dot(normalize(v_normal), LIGHT_DIRECTION), 0.0);
+    f_color = vec4(base_color.rgb * (AMBIENT + (1.0 - AMBIENT) * lambert), 1.0);
+}
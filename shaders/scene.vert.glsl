@@ -0,0 +1,26 @@
+#version 450
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec2 tex_coord;
+
+layout(location = 0) out vec3 v_normal;
+layout(location = 1) out vec2 v_tex_coord;
+layout(location = 2) flat out vec4 v_base_color;
+
+layout(set = 0, binding = 0) uniform Camera {
+    mat4 view;
+    mat4 projection;
+} camera;
+
+layout(push_constant) uniform Primitive {
+    mat4 model;
+    vec4 base_color;
+} primitive;
+
+void main() {
+    v_normal = transpose(inverse(mat3(primitive.model))) * normal;
+    v_tex_coord = tex_coord;
+    v_base_color = primitive.base_color;
+    gl_Position = camera.projection * camera.view * primitive.model * vec4(position, 1.0);
+}
@@ -0,0 +1,20 @@
+#version 450
+
+layout(location = 0) in vec2 position;
+layout(location = 1) in vec2 tex_coord;
+
+layout(location = 0) out vec2 v_tex_coord;
+
+layout(push_constant) uniform Quad {
+    vec2 size;
+    vec2 tex_coord_scale;
+    float angle;
+    float aspect;
+} quad;
+
+void main() {
+    mat2 rotation = mat2(cos(quad.angle), sin(quad.angle), -sin(quad.angle), cos(quad.angle));
+    v_tex_coord = (tex_coord - 0.5) * quad.tex_coord_scale + 0.5;
+    vec2 turned = rotation * (position * quad.size);
+    gl_Position = vec4(turned.x / quad.aspect, turned.y, 0.0, 1.0);
+}
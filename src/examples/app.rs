@@ -0,0 +1,418 @@
+use crate::examples::msaa::{
+    clear_values, color_framebuffer, color_render_pass, depth_clear_values, depth_framebuffer,
+    depth_render_pass, supported_samples,
+};
+use crate::examples::screenshot::Screenshot;
+use crate::examples::swapchain::{choose_present_mode, create_swapchain, toggle_vsync};
+use std::sync::Arc;
+use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
+use vulkano::device::{Device, Queue};
+use vulkano::framebuffer::{FramebufferAbstract, RenderPassAbstract};
+use vulkano::image::SwapchainImage;
+use vulkano::instance::Instance;
+use vulkano::pipeline::viewport::Viewport;
+use vulkano::swapchain::{self, AcquireError, PresentMode, SwapchainCreationError};
+use vulkano::sync;
+use vulkano::sync::{FenceSignalFuture, FlushError, GpuFuture};
+use vulkano_win::VkSurfaceBuild;
+use winit::event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent};
+use winit::event_loop::{ControlFlow, EventLoop};
+use winit::window::{Window, WindowBuilder};
+
+/// Upper bound of `AppOptions::frames_in_flight`, more only adds latency.
+pub const MAX_FRAMES_IN_FLIGHT: usize = 3;
+
+/// How `run_app` sets up the window.
+#[derive(Clone, Debug)]
+pub struct AppOptions {
+    pub title: String,
+    pub samples: u32,
+    pub present_mode: PresentMode,
+    /// How many frames may be recorded before the oldest one has finished on the GPU.
+    pub frames_in_flight: usize,
+    /// Whether the render pass is a `depth_render_pass` instead of a `color_render_pass`.
+    pub depth: bool,
+}
+
+/// What an app is created with.
+pub struct AppContext {
+    pub device: Arc<Device>,
+    pub queue: Arc<Queue>,
+    /// The render pass every frame draws in, pipelines are built for its subpass 0.
+    pub render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
+    /// The sample count the device supports that is closest to the requested one.
+    pub samples: u32,
+    /// The number of `FrameInfo::index` values, resources an app writes every frame should
+    /// exist that often.
+    pub frames_in_flight: usize,
+}
+
+/// The frame an app is recording.
+pub struct FrameInfo {
+    /// Which of the frames in flight this is. The GPU has finished the previous frame with the
+    /// same index, so resources only that frame used can be rewritten.
+    pub index: usize,
+    pub dimensions: [u32; 2],
+    pub present_mode: PresentMode,
+}
+
+/// An interactive demo run by `run_app`.
+///
+/// The runner owns the window, the swapchain and the frames in flight, quits on Escape, toggles
+/// vsync on V and saves a screenshot on F12, unless `wants_keyboard` says the app is taking the
+/// keys. Everything else goes through these hooks.
+pub trait App {
+    /// Called with every window event.
+    fn input(&mut self, _event: &WindowEvent) {}
+
+    /// Whether key presses are meant for the app, e.g. typed into a text field of its gui. The
+    /// runner's own key bindings are skipped while this is true.
+    fn wants_keyboard(&self) -> bool {
+        false
+    }
+
+    /// Called once per frame with the seconds since the last one.
+    fn update(&mut self, _delta_time: f32) {}
+
+    /// A present mode the app wants to switch to, asked for after every `update`.
+    fn requested_present_mode(&mut self) -> Option<PresentMode> {
+        None
+    }
+
+    /// Called with the window size before the first frame and whenever the swapchain changed.
+    fn resize(&mut self, _dimensions: [u32; 2]) {}
+
+    /// The color the render pass clears to.
+    fn clear_color(&self) -> [f32; 4] {
+        [0.0, 0.0, 0.0, 1.0]
+    }
+
+    /// Records work that has to happen outside of the render pass, e.g. compute dispatches.
+    fn prepare(
+        &mut self,
+        builder: AutoCommandBufferBuilder,
+        _frame: &FrameInfo,
+    ) -> AutoCommandBufferBuilder {
+        builder
+    }
+
+    /// Records drawing into the render pass `builder` is in.
+    fn record(
+        &mut self,
+        builder: AutoCommandBufferBuilder,
+        dynamic_state: &DynamicState,
+        frame: &FrameInfo,
+    ) -> AutoCommandBufferBuilder;
+}
+
+/// Opens a window and runs the app `create` returns in it until the window is closed.
+pub fn run_app<A, F>(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    instance: Arc<Instance>,
+    options: AppOptions,
+    create: F,
+) where
+    A: App + 'static,
+    F: FnOnce(&AppContext) -> A,
+{
+    let samples = supported_samples(device.physical_device(), options.samples, options.depth);
+    let frames_in_flight = options.frames_in_flight.max(1).min(MAX_FRAMES_IN_FLIGHT);
+
+    let events_loop = EventLoop::new();
+    let surface = WindowBuilder::new()
+        .with_title(options.title.clone())
+        .build_vk_surface(&events_loop, instance.clone())
+        .unwrap();
+
+    let supported_present_modes = surface
+        .capabilities(device.physical_device())
+        .expect("failed to get surface capabilities")
+        .present_modes;
+    let mut present_mode = choose_present_mode(supported_present_modes, options.present_mode);
+    let mut present_mode_changed = false;
+
+    let (mut swapchain, mut images) = create_swapchain(
+        device.clone(),
+        queue.clone(),
+        surface.clone(),
+        present_mode,
+        None,
+    )
+    .expect("failed to create swapchain");
+
+    let render_pass = if options.depth {
+        depth_render_pass(device.clone(), swapchain.format(), samples)
+    } else {
+        color_render_pass(device.clone(), swapchain.format(), samples)
+    };
+
+    let mut app = create(&AppContext {
+        device: device.clone(),
+        queue: queue.clone(),
+        render_pass: render_pass.clone(),
+        samples,
+        frames_in_flight,
+    });
+
+    let mut dynamic_state = DynamicState::none();
+
+    let mut framebuffers = window_size_dependent_setup(
+        device.clone(),
+        &images,
+        render_pass.clone(),
+        samples,
+        options.depth,
+        &mut dynamic_state,
+    );
+    app.resize(images[0].dimensions());
+
+    println!("{} frames in flight", frames_in_flight);
+
+    let mut fences: Vec<Option<Arc<FenceSignalFuture<Box<dyn GpuFuture>>>>> =
+        (0..frames_in_flight).map(|_| None).collect();
+    let mut frame_index = 0;
+
+    let mut previous_frame_end = Some(Box::new(sync::now(device.clone())) as Box<dyn GpuFuture>);
+    let mut recreate_swapchain = false;
+    let mut screenshot_requested = false;
+
+    let mut last_time = std::time::Instant::now();
+
+    events_loop.run(move |event, _, control_flow| {
+        *control_flow = ControlFlow::Poll;
+        match event {
+            Event::WindowEvent { event, .. } => {
+                match event {
+                    WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                    WindowEvent::Resized(_) => recreate_swapchain = true,
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(kc),
+                                ..
+                            },
+                        ..
+                    } if !app.wants_keyboard() => match kc {
+                        VirtualKeyCode::Escape => *control_flow = ControlFlow::Exit,
+                        VirtualKeyCode::V => {
+                            present_mode = toggle_vsync(supported_present_modes, present_mode);
+                            present_mode_changed = true;
+                            recreate_swapchain = true;
+                        }
+                        VirtualKeyCode::F12 => screenshot_requested = true,
+                        _ => (),
+                    },
+                    _ => (),
+                }
+
+                app.input(&event);
+            }
+            Event::MainEventsCleared => {
+                let time = std::time::Instant::now();
+                let delta_time = (time - last_time).as_secs_f32();
+                last_time = time;
+
+                app.update(delta_time);
+
+                if let Some(requested) = app.requested_present_mode() {
+                    let mode = choose_present_mode(supported_present_modes, requested);
+                    if mode != present_mode {
+                        present_mode = mode;
+                        present_mode_changed = true;
+                        recreate_swapchain = true;
+                    }
+                }
+
+                surface.window().set_title(
+                    format!(
+                        "{} - FPS: {:.2} ({:?}, V toggles vsync, F12 saves a screenshot)",
+                        options.title,
+                        1.0 / delta_time,
+                        present_mode
+                    )
+                    .as_str(),
+                );
+
+                surface.window().request_redraw();
+            }
+            Event::RedrawRequested(_) => {
+                previous_frame_end.as_mut().unwrap().cleanup_finished();
+
+                if recreate_swapchain {
+                    let dimensions: [u32; 2] = surface.window().inner_size().into();
+                    let recreated = if present_mode_changed {
+                        create_swapchain(
+                            device.clone(),
+                            queue.clone(),
+                            surface.clone(),
+                            present_mode,
+                            Some(swapchain.clone()),
+                        )
+                    } else {
+                        swapchain.recreate_with_dimensions(dimensions)
+                    };
+                    // Both flags stay set while the window is minimized.
+                    let (new_swapchain, new_images) = match recreated {
+                        Ok(r) => r,
+                        Err(SwapchainCreationError::UnsupportedDimensions) => return,
+                        Err(e) => panic!("Failed to recreate swapchain: {:?}", e),
+                    };
+                    present_mode_changed = false;
+
+                    swapchain = new_swapchain;
+                    images = new_images;
+                    framebuffers = window_size_dependent_setup(
+                        device.clone(),
+                        &images,
+                        render_pass.clone(),
+                        samples,
+                        options.depth,
+                        &mut dynamic_state,
+                    );
+                    app.resize(images[0].dimensions());
+                    recreate_swapchain = false;
+                }
+
+                let (image_num, suboptimal, acquire_future) =
+                    match swapchain::acquire_next_image(swapchain.clone(), None) {
+                        Ok(r) => r,
+                        Err(AcquireError::OutOfDate) => {
+                            recreate_swapchain = true;
+                            return;
+                        }
+                        Err(e) => panic!("Failed to acquire next image: {:?}", e),
+                    };
+
+                if suboptimal {
+                    recreate_swapchain = true;
+                }
+
+                // Only the frame that used this index last has to be done, the others keep the
+                // GPU busy while this one is recorded.
+                let frame = FrameInfo {
+                    index: frame_index,
+                    dimensions: images[0].dimensions(),
+                    present_mode,
+                };
+                frame_index = (frame_index + 1) % frames_in_flight;
+
+                if let Some(fence) = fences[frame.index].take() {
+                    fence.wait(None).unwrap();
+                }
+
+                let clear_color = app.clear_color();
+                let clear_values = if options.depth {
+                    depth_clear_values(samples, clear_color)
+                } else {
+                    clear_values(samples, clear_color)
+                };
+
+                let builder = AutoCommandBufferBuilder::primary_one_time_submit(
+                    device.clone(),
+                    queue.family(),
+                )
+                .unwrap();
+                let builder = app
+                    .prepare(builder, &frame)
+                    .begin_render_pass(framebuffers[image_num].clone(), false, clear_values)
+                    .unwrap();
+                let mut command_buffer = app
+                    .record(builder, &dynamic_state, &frame)
+                    .end_render_pass()
+                    .unwrap();
+
+                let screenshot = if screenshot_requested {
+                    screenshot_requested = false;
+                    Some(Screenshot::new(device.clone(), &images[image_num]))
+                } else {
+                    None
+                };
+                if let Some(screenshot) = &screenshot {
+                    command_buffer =
+                        screenshot.copy_from(command_buffer, images[image_num].clone());
+                }
+
+                let command_buffer = command_buffer.build().unwrap();
+
+                let future = (Box::new(
+                    previous_frame_end
+                        .take()
+                        .unwrap()
+                        .join(acquire_future)
+                        .then_execute(queue.clone(), command_buffer)
+                        .unwrap()
+                        .then_swapchain_present(queue.clone(), swapchain.clone(), image_num),
+                ) as Box<dyn GpuFuture>)
+                    .then_signal_fence_and_flush();
+
+                match future {
+                    Ok(future) => {
+                        let fence = Arc::new(future);
+                        if let Some(screenshot) = screenshot {
+                            fence.wait(None).unwrap();
+                            screenshot.save();
+                        }
+                        fences[frame.index] = Some(fence.clone());
+                        previous_frame_end = Some(Box::new(fence) as Box<_>);
+                    }
+                    Err(FlushError::OutOfDate) => {
+                        recreate_swapchain = true;
+                        previous_frame_end = Some(Box::new(sync::now(device.clone())) as Box<_>);
+                    }
+                    Err(e) => {
+                        println!("Failed to flush future {:?}", e);
+                        previous_frame_end = Some(Box::new(sync::now(device.clone())) as Box<_>);
+                    }
+                }
+            }
+            _ => (),
+        }
+    });
+}
+
+fn window_size_dependent_setup(
+    device: Arc<Device>,
+    images: &[Arc<SwapchainImage<Window>>],
+    render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
+    samples: u32,
+    depth: bool,
+    dynamic_state: &mut DynamicState,
+) -> Vec<Arc<dyn FramebufferAbstract + Send + Sync>> {
+    let dimensions = images[0].dimensions();
+
+    let viewport = Viewport {
+        origin: [0.0, 0.0],
+        dimensions: [dimensions[0] as f32, dimensions[1] as f32],
+        depth_range: 0.0..1.0,
+    };
+
+    dynamic_state.viewports = Some(vec![viewport]);
+
+    images
+        .iter()
+        .map(|image| {
+            let format = image.swapchain().format();
+            if depth {
+                depth_framebuffer(
+                    device.clone(),
+                    render_pass.clone(),
+                    image.clone(),
+                    dimensions,
+                    format,
+                    samples,
+                )
+            } else {
+                color_framebuffer(
+                    device.clone(),
+                    render_pass.clone(),
+                    image.clone(),
+                    dimensions,
+                    format,
+                    samples,
+                )
+            }
+        })
+        .collect::<Vec<_>>()
+}
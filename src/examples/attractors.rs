@@ -0,0 +1,227 @@
+use crate::examples::camera::Camera2d;
+use crate::examples::hud::Hud;
+
+/// Attractors the physics shader can read in one dispatch.
+pub const MAX_ATTRACTORS: usize = 64;
+
+/// Pixels around an attractor's marker that still pick it.
+const PICK_RADIUS: f32 = 10.0;
+
+/// How an attractor moves around its anchor.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Path {
+    Still,
+    Circle,
+    FigureEight,
+    /// Back and forth along the x axis.
+    Line,
+}
+
+impl Path {
+    pub const ALL: [Path; 4] = [Path::Still, Path::Circle, Path::FigureEight, Path::Line];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Path::Still => "still",
+            Path::Circle => "circle",
+            Path::FigureEight => "figure eight",
+            Path::Line => "line",
+        }
+    }
+
+    /// Offset from the anchor at `phase` radians along a path of size 1.
+    fn offset(self, phase: f32) -> [f32; 2] {
+        match self {
+            Path::Still => [0.0, 0.0],
+            Path::Circle => [phase.cos(), phase.sin()],
+            Path::FigureEight => [phase.sin(), (2.0 * phase).sin() * 0.5],
+            Path::Line => [phase.sin(), 0.0],
+        }
+    }
+}
+
+/// A point that pulls particles in, or pushes them away with a negative mass.
+#[derive(Copy, Clone, Debug)]
+pub struct Attractor {
+    /// Where the attractor sits, or the center of its path.
+    pub anchor: [f32; 2],
+    pub mass: f32,
+    /// Distances below this count as this, so the pull stays finite at the center.
+    pub radius: f32,
+    /// The pull falls off with the distance to this power.
+    pub falloff: f32,
+    pub path: Path,
+    /// Size of the path in world units.
+    pub path_size: f32,
+    /// Radians along the path per simulated second.
+    pub path_speed: f32,
+}
+
+impl Attractor {
+    pub fn new(anchor: [f32; 2], mass: f32) -> Attractor {
+        Attractor {
+            anchor,
+            mass,
+            radius: 0.01,
+            falloff: 1.0,
+            path: Path::Still,
+            path_size: 0.3,
+            path_speed: 1.0,
+        }
+    }
+
+    /// Where the attractor is `time` simulated seconds in.
+    pub fn position(&self, time: f32) -> [f32; 2] {
+        let [x, y] = self.path.offset(time * self.path_speed);
+        [
+            self.anchor[0] + x * self.path_size,
+            self.anchor[1] + y * self.path_size,
+        ]
+    }
+
+    /// The attractor as the physics shader reads it at `time`.
+    pub fn gpu(&self, time: f32) -> GpuAttractor {
+        GpuAttractor {
+            position: self.position(time),
+            mass: self.mass,
+            radius: self.radius,
+            falloff: self.falloff,
+            padding: 0.0,
+        }
+    }
+}
+
+/// `Attractor` in particle_physics.comp.glsl, padded to the std430 array stride.
+#[derive(Default, Copy, Clone)]
+#[repr(C)]
+pub struct GpuAttractor {
+    pub position: [f32; 2],
+    pub mass: f32,
+    pub radius: f32,
+    pub falloff: f32,
+    pub padding: f32,
+}
+
+/// The attractors placed in the view and which one is being edited.
+///
+/// Left clicks place or drag attractors, middle clicks remove them. Every pixel argument is a
+/// window position, converted with the camera the particles are drawn with.
+#[derive(Default)]
+pub struct Attractors {
+    pub list: Vec<Attractor>,
+    pub selected: Option<usize>,
+    dragging: bool,
+    /// Simulated seconds the paths have advanced.
+    time: f32,
+}
+
+impl Attractors {
+    pub fn advance(&mut self, seconds: f32) {
+        self.time += seconds;
+    }
+
+    /// The attractor whose marker is under `pixel`, the last placed first.
+    pub fn pick(&self, pixel: [f32; 2], camera: &Camera2d, dimensions: [u32; 2]) -> Option<usize> {
+        self.list.iter().rposition(|attractor| {
+            let [x, y] = camera.world_to_screen(attractor.position(self.time), dimensions);
+            (x - pixel[0]).abs() <= PICK_RADIUS && (y - pixel[1]).abs() <= PICK_RADIUS
+        })
+    }
+
+    /// Selects and starts dragging the attractor under `pixel`, or places one of `mass` there.
+    pub fn press(&mut self, pixel: [f32; 2], camera: &Camera2d, dimensions: [u32; 2], mass: f32) {
+        if let Some(index) = self.pick(pixel, camera, dimensions) {
+            self.selected = Some(index);
+            self.dragging = true;
+        } else if self.list.len() < MAX_ATTRACTORS {
+            let anchor = camera.screen_to_world(pixel, dimensions);
+            self.list.push(Attractor::new(anchor, mass));
+            self.selected = Some(self.list.len() - 1);
+        }
+    }
+
+    /// Moves the dragged attractor's anchor to `pixel`.
+    pub fn drag(&mut self, pixel: [f32; 2], camera: &Camera2d, dimensions: [u32; 2]) {
+        if let (true, Some(index)) = (self.dragging, self.selected) {
+            self.list[index].anchor = camera.screen_to_world(pixel, dimensions);
+        }
+    }
+
+    pub fn release(&mut self) {
+        self.dragging = false;
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        self.list.remove(index);
+        self.dragging = false;
+        self.selected = match self.selected {
+            Some(selected) if selected == index => None,
+            Some(selected) if selected > index => Some(selected - 1),
+            selected => selected,
+        };
+    }
+
+    pub fn clear(&mut self) {
+        self.list.clear();
+        self.selected = None;
+        self.dragging = false;
+    }
+
+    /// Fills `out` from the front and returns how many attractors it holds.
+    pub fn write_gpu(&self, out: &mut [GpuAttractor]) -> usize {
+        for (slot, attractor) in out.iter_mut().zip(&self.list) {
+            *slot = attractor.gpu(self.time);
+        }
+        self.list.len().min(out.len())
+    }
+
+    /// Queues a marker per attractor, green ones pull and red ones push.
+    pub fn draw(&self, hud: &mut Hud, camera: &Camera2d, dimensions: [u32; 2]) {
+        for (index, attractor) in self.list.iter().enumerate() {
+            let [x, y] = camera.world_to_screen(attractor.position(self.time), dimensions);
+
+            if self.selected == Some(index) {
+                hud.rect([x - 6.0, y - 6.0], [x + 6.0, y + 6.0], [1.0, 1.0, 1.0, 1.0]);
+            }
+
+            let color = if attractor.mass < 0.0 {
+                [1.0, 0.2, 0.2, 1.0]
+            } else {
+                [0.2, 1.0, 0.2, 1.0]
+            };
+            hud.rect([x - 4.0, y - 4.0], [x + 4.0, y + 4.0], color);
+        }
+    }
+
+    /// Widgets for the selected attractor.
+    pub fn editor(&mut self, ui: &mut egui::Ui) {
+        let index = match self.selected {
+            Some(index) => index,
+            None => {
+                ui.label("Left click places or drags attractors, middle click removes them");
+                return;
+            }
+        };
+
+        let attractor = &mut self.list[index];
+        ui.add(egui::Slider::new(&mut attractor.mass, -5.0..=5.0).text("mass"));
+        ui.add(
+            egui::Slider::new(&mut attractor.radius, 0.001..=0.5)
+                .logarithmic(true)
+                .text("radius"),
+        );
+        ui.add(egui::Slider::new(&mut attractor.falloff, 0.0..=3.0).text("falloff"));
+        ui.horizontal(|ui| {
+            for &path in &Path::ALL {
+                ui.radio_value(&mut attractor.path, path, path.name());
+            }
+        });
+        if attractor.path != Path::Still {
+            ui.add(egui::Slider::new(&mut attractor.path_size, 0.0..=1.0).text("path size"));
+            ui.add(egui::Slider::new(&mut attractor.path_speed, -5.0..=5.0).text("path speed"));
+        }
+        if ui.button("Remove").clicked() {
+            self.remove(index);
+        }
+    }
+}
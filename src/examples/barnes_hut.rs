@@ -0,0 +1,690 @@
+use crate::examples::nbody::{NBody, NBodyParams, Solver};
+use crate::examples::vulkano_particles::Vertex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::Arc;
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer, DeviceLocalBuffer};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBuffer};
+use vulkano::descriptor::descriptor_set::{DescriptorSet, PersistentDescriptorSet};
+use vulkano::descriptor::PipelineLayoutAbstract;
+use vulkano::device::{Device, Queue};
+use vulkano::pipeline::{ComputePipeline, ComputePipelineAbstract};
+use vulkano::sync::GpuFuture;
+
+/// Invocations per workgroup of the passes over all bodies or nodes.
+const WORKGROUP_SIZE: u32 = 1024;
+
+/// Keys per workgroup of the histogram and scatter passes.
+const SORT_BLOCK_SIZE: u32 = 256;
+
+/// Counts per workgroup of the scan passes.
+const SCAN_BLOCK_SIZE: u32 = 1024;
+
+/// Digits per radix sort pass and their bits.
+const RADIX: u32 = 16;
+const RADIX_BITS: u32 = 4;
+
+/// Sort passes over the 30 bit Morton codes. Even, so the sorted keys end up in the buffers they
+/// started in.
+const SORT_PASSES: u32 = 8;
+
+/// Invocations per workgroup of the force shader.
+const FORCE_WORKGROUP_SIZE: u32 = 256;
+
+/// Bodies per scene of `barnes_hut_check`. The largest one has more digit counts than a single
+/// scan block holds.
+const CHECK_COUNTS: [u32; 7] = [1, 2, 3, 100, 1000, 5000, 20_000];
+
+type DynComputePipeline = Arc<dyn ComputePipelineAbstract + Send + Sync>;
+
+fn groups(count: u32, size: u32) -> u32 {
+    (count + size - 1) / size
+}
+
+/// The buffers of one particle buffer's tree, bound for every pass.
+struct Tree {
+    particle_count: u32,
+    bounds_set: Arc<dyn DescriptorSet + Send + Sync>,
+    /// Reducing the partial bounds level by level down to one box, with the workgroups of each.
+    bounds_reduce_sets: Vec<(Arc<dyn DescriptorSet + Send + Sync>, u32)>,
+    morton_set: Arc<dyn DescriptorSet + Send + Sync>,
+    /// Counting the keys in the first and in the second key buffer.
+    histogram_sets: [Arc<dyn DescriptorSet + Send + Sync>; 2],
+    /// Scanning the counts in blocks, then the block totals in blocks and so on until a single
+    /// block is left, with the workgroups of each level.
+    scan_sets: Vec<(Arc<dyn DescriptorSet + Send + Sync>, u32)>,
+    /// Adding the scanned block totals back into every level but the last.
+    scan_add_sets: Vec<(Arc<dyn DescriptorSet + Send + Sync>, u32)>,
+    /// Sorting from the first into the second buffers and back.
+    scatter_sets: [Arc<dyn DescriptorSet + Send + Sync>; 2],
+    hierarchy_set: Arc<dyn DescriptorSet + Send + Sync>,
+    quadtree_set: Arc<dyn DescriptorSet + Send + Sync>,
+    summarize_set: Arc<dyn DescriptorSet + Send + Sync>,
+    force_set: Arc<dyn DescriptorSet + Send + Sync>,
+}
+
+/// Gravity approximated with a quadtree, O(n log n) per step.
+///
+/// The tree is rebuilt on the GPU every step. The bodies get Morton codes within their bounding
+/// box and are radix sorted by them. A binary radix tree is built over the sorted codes (Karras)
+/// and collapsed into a quadtree, merging the binary nodes that split within one level of the
+/// quadtree, so every node has the nonempty quadrants of its cell as children. A pass from the
+/// leaves to the root sums up the mass, center of mass and bounds of every node. The force pass
+/// then walks the quadtree for every body and takes nodes that look small enough from the body
+/// as a single body.
+pub struct BarnesHut {
+    queue: Arc<Queue>,
+    bounds_pipeline: DynComputePipeline,
+    bounds_reduce_pipeline: DynComputePipeline,
+    morton_pipeline: DynComputePipeline,
+    histogram_pipeline: DynComputePipeline,
+    scan_pipeline: DynComputePipeline,
+    scan_add_pipeline: DynComputePipeline,
+    scatter_pipeline: DynComputePipeline,
+    hierarchy_pipeline: DynComputePipeline,
+    quadtree_pipeline: DynComputePipeline,
+    summarize_pipeline: DynComputePipeline,
+    force_pipeline: DynComputePipeline,
+    /// A single body feels no gravity, below two there is no tree.
+    tree: Option<Tree>,
+}
+
+impl BarnesHut {
+    pub fn new(
+        queue: Arc<Queue>,
+        particles: Arc<DeviceLocalBuffer<[Vertex]>>,
+        particle_count: u32,
+    ) -> BarnesHut {
+        let device = queue.device().clone();
+
+        macro_rules! pipeline {
+            ($module:ident) => {{
+                let shader =
+                    $module::Shader::load(device.clone()).expect("failed to create shader module");
+                Arc::new(
+                    ComputePipeline::new(device.clone(), &shader.main_entry_point(), &())
+                        .expect("failed to create barnes-hut pipeline"),
+                ) as DynComputePipeline
+            }};
+        }
+
+        let mut barnes_hut = BarnesHut {
+            bounds_pipeline: pipeline!(cs_bounds),
+            bounds_reduce_pipeline: pipeline!(cs_bounds_reduce),
+            morton_pipeline: pipeline!(cs_morton),
+            histogram_pipeline: pipeline!(cs_sort_histogram),
+            scan_pipeline: pipeline!(cs_sort_scan),
+            scan_add_pipeline: pipeline!(cs_sort_scan_add),
+            scatter_pipeline: pipeline!(cs_sort_scatter),
+            hierarchy_pipeline: pipeline!(cs_hierarchy),
+            quadtree_pipeline: pipeline!(cs_quadtree),
+            summarize_pipeline: pipeline!(cs_summarize),
+            force_pipeline: pipeline!(cs_force),
+            queue,
+            tree: None,
+        };
+        barnes_hut.set_particles(particles, particle_count);
+        barnes_hut
+    }
+
+    /// Builds trees over `particles` from now on, after a respawn.
+    pub fn set_particles(&mut self, particles: Arc<DeviceLocalBuffer<[Vertex]>>, count: u32) {
+        self.tree = if count < 2 {
+            None
+        } else {
+            Some(self.create_tree(particles, count))
+        };
+    }
+
+    fn storage_buffer<T: Send + Sync + 'static>(&self, len: u32) -> Arc<DeviceLocalBuffer<[T]>> {
+        DeviceLocalBuffer::array(
+            self.queue.device().clone(),
+            len as usize,
+            BufferUsage {
+                storage_buffer: true,
+                ..BufferUsage::none()
+            },
+            vec![self.queue.family()],
+        )
+        .expect("failed to create tree buffer")
+    }
+
+    fn create_tree(&self, particles: Arc<DeviceLocalBuffer<[Vertex]>>, count: u32) -> Tree {
+        let internal_count = count - 1;
+        let node_count = internal_count + count;
+
+        // Min in xy and max in zw, per workgroup.
+        let partials = self.storage_buffer::<[f32; 4]>(groups(count, WORKGROUP_SIZE));
+        let keys = [
+            self.storage_buffer::<u32>(count),
+            self.storage_buffer::<u32>(count),
+        ];
+        let values = [
+            self.storage_buffer::<u32>(count),
+            self.storage_buffer::<u32>(count),
+        ];
+        let counts = self.storage_buffer::<u32>(RADIX * groups(count, SORT_BLOCK_SIZE));
+        let children = self.storage_buffer::<[i32; 2]>(internal_count);
+        let parents = self.storage_buffer::<i32>(node_count);
+        let visits = self.storage_buffer::<u32>(internal_count);
+        let prefixes = self.storage_buffer::<i32>(internal_count);
+        let quad_children = self.storage_buffer::<[i32; 4]>(internal_count);
+        // `Node` in barnes_hut_common.glsl, the mass and the bounds of every node.
+        let nodes = self.storage_buffer::<[[f32; 4]; 2]>(node_count);
+
+        let layout = |pipeline: &DynComputePipeline| {
+            pipeline.layout().descriptor_set_layout(0).unwrap().clone()
+        };
+
+        let histogram_set = |keys: Arc<DeviceLocalBuffer<[u32]>>| {
+            Arc::new(
+                PersistentDescriptorSet::start(layout(&self.histogram_pipeline))
+                    .add_buffer(keys)
+                    .unwrap()
+                    .add_buffer(counts.clone())
+                    .unwrap()
+                    .build()
+                    .unwrap(),
+            ) as Arc<dyn DescriptorSet + Send + Sync>
+        };
+
+        let scatter_set = |from: usize, to: usize| {
+            Arc::new(
+                PersistentDescriptorSet::start(layout(&self.scatter_pipeline))
+                    .add_buffer(keys[from].clone())
+                    .unwrap()
+                    .add_buffer(values[from].clone())
+                    .unwrap()
+                    .add_buffer(counts.clone())
+                    .unwrap()
+                    .add_buffer(keys[to].clone())
+                    .unwrap()
+                    .add_buffer(values[to].clone())
+                    .unwrap()
+                    .build()
+                    .unwrap(),
+            ) as Arc<dyn DescriptorSet + Send + Sync>
+        };
+
+        // Every reduce pass leaves one box per workgroup, the last one leaves the overall bounds.
+        let mut bounds_reduce_sets = Vec::new();
+        let mut bounds = partials.clone();
+        let mut len = groups(count, WORKGROUP_SIZE);
+        while len > 1 {
+            let workgroups = groups(len, WORKGROUP_SIZE);
+            let reduced = self.storage_buffer::<[f32; 4]>(workgroups);
+            let set = PersistentDescriptorSet::start(layout(&self.bounds_reduce_pipeline))
+                .add_buffer(bounds)
+                .unwrap()
+                .add_buffer(reduced.clone())
+                .unwrap()
+                .build()
+                .unwrap();
+            bounds_reduce_sets.push((
+                Arc::new(set) as Arc<dyn DescriptorSet + Send + Sync>,
+                workgroups,
+            ));
+            bounds = reduced;
+            len = workgroups;
+        }
+
+        // The block totals of every level are the values of the next one.
+        let mut scan_sets = Vec::new();
+        let mut scan_add_sets = Vec::new();
+        let mut level = counts.clone();
+        let mut len = RADIX * groups(count, SORT_BLOCK_SIZE);
+        loop {
+            let blocks = groups(len, SCAN_BLOCK_SIZE);
+            let sums = self.storage_buffer::<u32>(blocks);
+            let level_set = |pipeline: &DynComputePipeline| {
+                Arc::new(
+                    PersistentDescriptorSet::start(layout(pipeline))
+                        .add_buffer(level.clone())
+                        .unwrap()
+                        .add_buffer(sums.clone())
+                        .unwrap()
+                        .build()
+                        .unwrap(),
+                ) as Arc<dyn DescriptorSet + Send + Sync>
+            };
+
+            scan_sets.push((level_set(&self.scan_pipeline), blocks));
+            if blocks == 1 {
+                break;
+            }
+            scan_add_sets.push((level_set(&self.scan_add_pipeline), blocks));
+            level = sums;
+            len = blocks;
+        }
+
+        Tree {
+            particle_count: count,
+            bounds_set: Arc::new(
+                PersistentDescriptorSet::start(layout(&self.bounds_pipeline))
+                    .add_buffer(particles.clone())
+                    .unwrap()
+                    .add_buffer(partials)
+                    .unwrap()
+                    .build()
+                    .unwrap(),
+            ),
+            bounds_reduce_sets,
+            morton_set: Arc::new(
+                PersistentDescriptorSet::start(layout(&self.morton_pipeline))
+                    .add_buffer(particles.clone())
+                    .unwrap()
+                    .add_buffer(bounds)
+                    .unwrap()
+                    .add_buffer(keys[0].clone())
+                    .unwrap()
+                    .add_buffer(values[0].clone())
+                    .unwrap()
+                    .build()
+                    .unwrap(),
+            ),
+            histogram_sets: [
+                histogram_set(keys[0].clone()),
+                histogram_set(keys[1].clone()),
+            ],
+            scan_sets,
+            scan_add_sets,
+            scatter_sets: [scatter_set(0, 1), scatter_set(1, 0)],
+            hierarchy_set: Arc::new(
+                PersistentDescriptorSet::start(layout(&self.hierarchy_pipeline))
+                    .add_buffer(keys[0].clone())
+                    .unwrap()
+                    .add_buffer(children.clone())
+                    .unwrap()
+                    .add_buffer(parents.clone())
+                    .unwrap()
+                    .add_buffer(visits.clone())
+                    .unwrap()
+                    .add_buffer(prefixes.clone())
+                    .unwrap()
+                    .build()
+                    .unwrap(),
+            ),
+            quadtree_set: Arc::new(
+                PersistentDescriptorSet::start(layout(&self.quadtree_pipeline))
+                    .add_buffer(children.clone())
+                    .unwrap()
+                    .add_buffer(prefixes)
+                    .unwrap()
+                    .add_buffer(quad_children.clone())
+                    .unwrap()
+                    .build()
+                    .unwrap(),
+            ),
+            summarize_set: Arc::new(
+                PersistentDescriptorSet::start(layout(&self.summarize_pipeline))
+                    .add_buffer(particles.clone())
+                    .unwrap()
+                    .add_buffer(values[0].clone())
+                    .unwrap()
+                    .add_buffer(children)
+                    .unwrap()
+                    .add_buffer(parents)
+                    .unwrap()
+                    .add_buffer(visits)
+                    .unwrap()
+                    .add_buffer(nodes.clone())
+                    .unwrap()
+                    .build()
+                    .unwrap(),
+            ),
+            force_set: Arc::new(
+                PersistentDescriptorSet::start(layout(&self.force_pipeline))
+                    .add_buffer(particles)
+                    .unwrap()
+                    .add_buffer(values[0].clone())
+                    .unwrap()
+                    .add_buffer(quad_children)
+                    .unwrap()
+                    .add_buffer(nodes)
+                    .unwrap()
+                    .build()
+                    .unwrap(),
+            ),
+        }
+    }
+
+    /// Records building the tree over the current positions and kicking every velocity with the
+    /// gravity of `delta_time` seconds the tree approximates.
+    pub fn kick(
+        &self,
+        builder: AutoCommandBufferBuilder,
+        params: NBodyParams,
+        delta_time: f32,
+    ) -> AutoCommandBufferBuilder {
+        let tree = match &self.tree {
+            Some(tree) => tree,
+            None => return builder,
+        };
+        let count = tree.particle_count;
+        let sort_groups = groups(count, SORT_BLOCK_SIZE);
+
+        let mut builder = builder
+            .dispatch(
+                [groups(count, WORKGROUP_SIZE), 1, 1],
+                self.bounds_pipeline.clone(),
+                tree.bounds_set.clone(),
+                (),
+            )
+            .unwrap();
+        for (set, workgroups) in &tree.bounds_reduce_sets {
+            builder = builder
+                .dispatch(
+                    [*workgroups, 1, 1],
+                    self.bounds_reduce_pipeline.clone(),
+                    set.clone(),
+                    (),
+                )
+                .unwrap();
+        }
+        builder = builder
+            .dispatch(
+                [groups(count, WORKGROUP_SIZE), 1, 1],
+                self.morton_pipeline.clone(),
+                tree.morton_set.clone(),
+                (),
+            )
+            .unwrap();
+
+        for pass in 0..SORT_PASSES {
+            let from = pass as usize % 2;
+            let shift = pass * RADIX_BITS;
+            builder = builder
+                .dispatch(
+                    [sort_groups, 1, 1],
+                    self.histogram_pipeline.clone(),
+                    tree.histogram_sets[from].clone(),
+                    cs_sort_histogram::ty::Params { shift },
+                )
+                .unwrap();
+            for (set, blocks) in &tree.scan_sets {
+                builder = builder
+                    .dispatch([*blocks, 1, 1], self.scan_pipeline.clone(), set.clone(), ())
+                    .unwrap();
+            }
+            for (set, blocks) in tree.scan_add_sets.iter().rev() {
+                builder = builder
+                    .dispatch(
+                        [*blocks, 1, 1],
+                        self.scan_add_pipeline.clone(),
+                        set.clone(),
+                        (),
+                    )
+                    .unwrap();
+            }
+            builder = builder
+                .dispatch(
+                    [sort_groups, 1, 1],
+                    self.scatter_pipeline.clone(),
+                    tree.scatter_sets[from].clone(),
+                    cs_sort_scatter::ty::Params { shift },
+                )
+                .unwrap();
+        }
+
+        builder
+            .dispatch(
+                [groups(count - 1, WORKGROUP_SIZE), 1, 1],
+                self.hierarchy_pipeline.clone(),
+                tree.hierarchy_set.clone(),
+                (),
+            )
+            .unwrap()
+            .dispatch(
+                [groups(count - 1, WORKGROUP_SIZE), 1, 1],
+                self.quadtree_pipeline.clone(),
+                tree.quadtree_set.clone(),
+                (),
+            )
+            .unwrap()
+            .dispatch(
+                [groups(count, WORKGROUP_SIZE), 1, 1],
+                self.summarize_pipeline.clone(),
+                tree.summarize_set.clone(),
+                cs_summarize::ty::Params {
+                    body_mass: params.body_mass,
+                },
+            )
+            .unwrap()
+            .dispatch(
+                [groups(count, FORCE_WORKGROUP_SIZE), 1, 1],
+                self.force_pipeline.clone(),
+                tree.force_set.clone(),
+                cs_force::ty::Params {
+                    delta_time,
+                    gravity: params.gravity,
+                    softening: params.softening,
+                    theta: params.theta,
+                },
+            )
+            .unwrap()
+    }
+}
+
+/// Seeded clusters of bodies at rest, so one kick of a second leaves the accelerations in the
+/// velocities.
+fn check_bodies(count: u32) -> Vec<Vertex> {
+    let mut rng = StdRng::seed_from_u64(count as u64);
+    let clusters = (0..4)
+        .map(|_| {
+            (
+                [rng.gen_range(-0.8, 0.8), rng.gen_range(-0.8, 0.8)],
+                rng.gen_range(0.05, 0.3),
+            )
+        })
+        .collect::<Vec<([f32; 2], f32)>>();
+
+    (0..count)
+        .map(|_| {
+            let (center, radius) = clusters[rng.gen_range(0, clusters.len())];
+            let angle = rng.gen_range(0.0, std::f32::consts::PI * 2.0);
+            let distance = radius * rng.gen::<f32>().sqrt();
+            Vertex {
+                position: [
+                    center[0] + angle.cos() * distance,
+                    center[1] + angle.sin() * distance,
+                ],
+                velocity: [0.0, 0.0],
+            }
+        })
+        .collect()
+}
+
+/// The velocities of `bodies` after one kick of a second with `solver`.
+fn kick_once(
+    device: &Arc<Device>,
+    queue: &Arc<Queue>,
+    bodies: &[Vertex],
+    solver: Solver,
+    theta: f32,
+) -> Vec<[f32; 2]> {
+    let count = bodies.len() as u32;
+
+    let upload = CpuAccessibleBuffer::from_iter(
+        device.clone(),
+        BufferUsage::transfer_source(),
+        false,
+        bodies.iter().cloned(),
+    )
+    .unwrap();
+    let readback = CpuAccessibleBuffer::from_iter(
+        device.clone(),
+        BufferUsage::transfer_destination(),
+        false,
+        bodies.iter().cloned(),
+    )
+    .unwrap();
+    let particles = DeviceLocalBuffer::array(
+        device.clone(),
+        bodies.len(),
+        BufferUsage {
+            storage_buffer: true,
+            transfer_source: true,
+            transfer_destination: true,
+            ..BufferUsage::none()
+        },
+        vec![queue.family()],
+    )
+    .unwrap();
+
+    let nbody = NBody::new(queue.clone(), particles.clone(), count, 1);
+    let params = NBodyParams {
+        gravity: 1.0,
+        softening: 0.02,
+        body_mass: 1.0 / count as f32,
+        theta,
+    };
+
+    let builder = AutoCommandBufferBuilder::new(device.clone(), queue.family())
+        .unwrap()
+        .copy_buffer(upload, particles.clone())
+        .unwrap();
+    nbody
+        .kick(builder, params, 1.0, solver)
+        .copy_buffer(particles, readback.clone())
+        .unwrap()
+        .build()
+        .unwrap()
+        .execute(queue.clone())
+        .unwrap()
+        .then_signal_fence_and_flush()
+        .unwrap()
+        .wait(None)
+        .unwrap();
+
+    let velocities = readback
+        .read()
+        .unwrap()
+        .iter()
+        .map(|body| body.velocity)
+        .collect();
+    velocities
+}
+
+/// Kicks a few seeded scenes with the direct sum and with the tree, and panics if the tree's
+/// accelerations stray too far from the direct ones. Opening angle 0 opens every node and has to
+/// match up to rounding.
+pub fn barnes_hut_check(device: Arc<Device>, queue: Arc<Queue>) {
+    // Opening angle, largest mean and largest maximum error.
+    let cases = [(0.0, 1e-4, 1e-3), (0.5, 0.02, 0.1)];
+
+    for &count in &CHECK_COUNTS {
+        let bodies = check_bodies(count);
+        let direct = kick_once(&device, &queue, &bodies, Solver::Direct, 0.0);
+
+        // Errors are relative to the RMS acceleration, so a body that happens to feel no net
+        // pull does not blow them up.
+        let squares = direct
+            .iter()
+            .map(|a| a[0] * a[0] + a[1] * a[1])
+            .sum::<f32>();
+        let rms = (squares / count as f32).sqrt().max(1e-30);
+
+        for &(theta, max_mean, max_error) in &cases {
+            let tree = kick_once(&device, &queue, &bodies, Solver::BarnesHut, theta);
+            let errors = direct
+                .iter()
+                .zip(&tree)
+                .map(|(a, b)| (a[0] - b[0]).hypot(a[1] - b[1]) / rms)
+                .collect::<Vec<f32>>();
+            let mean = errors.iter().sum::<f32>() / count as f32;
+            let max = errors.iter().cloned().fold(0.0, f32::max);
+
+            println!(
+                "{} bodies, theta {}: mean error {:.2e}, max error {:.2e}",
+                count, theta, mean, max
+            );
+            assert!(
+                mean <= max_mean && max <= max_error,
+                "{} bodies at theta {} are off by {:.2e} on average and {:.2e} at most",
+                count,
+                theta,
+                mean,
+                max
+            );
+        }
+    }
+
+    println!("BARNES-HUT YAY OKAY!");
+}
+
+mod cs_bounds {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/bh_bounds.comp.glsl"
+    }
+}
+
+mod cs_bounds_reduce {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/bh_bounds_reduce.comp.glsl"
+    }
+}
+
+mod cs_morton {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/bh_morton.comp.glsl"
+    }
+}
+
+mod cs_sort_histogram {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/bh_sort_histogram.comp.glsl"
+    }
+}
+
+mod cs_sort_scan {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/bh_sort_scan.comp.glsl"
+    }
+}
+
+mod cs_sort_scan_add {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/bh_sort_scan_add.comp.glsl"
+    }
+}
+
+mod cs_sort_scatter {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/bh_sort_scatter.comp.glsl"
+    }
+}
+
+mod cs_hierarchy {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/bh_hierarchy.comp.glsl"
+    }
+}
+
+mod cs_quadtree {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/bh_quadtree.comp.glsl"
+    }
+}
+
+mod cs_summarize {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/bh_summarize.comp.glsl"
+    }
+}
+
+mod cs_force {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/bh_force.comp.glsl"
+    }
+}
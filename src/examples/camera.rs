@@ -0,0 +1,74 @@
+use cgmath::{Matrix4, Vector3};
+
+/// A 2D camera looking at `center`, showing `2 / zoom` world units across the window height.
+///
+/// World coordinates follow Vulkan's clip space with y pointing down, so at the default zoom
+/// the world [-1, 1] fills the window height and the width keeps the pixels square.
+#[derive(Copy, Clone, Debug)]
+pub struct Camera2d {
+    pub center: [f32; 2],
+    pub zoom: f32,
+    /// Height over width of the window.
+    pub aspect: f32,
+}
+
+impl Camera2d {
+    pub fn new(aspect: f32) -> Camera2d {
+        Camera2d {
+            center: [0.0, 0.0],
+            zoom: 1.0,
+            aspect,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Camera2d::new(self.aspect);
+    }
+
+    /// Maps world coordinates to clip space.
+    pub fn view_matrix(&self) -> Matrix4<f32> {
+        Matrix4::from_nonuniform_scale(self.zoom * self.aspect, self.zoom, 1.0)
+            * Matrix4::from_translation(Vector3::new(-self.center[0], -self.center[1], 0.0))
+    }
+
+    /// The world position under `pixel` of a window of `dimensions` pixels.
+    pub fn screen_to_world(&self, pixel: [f32; 2], dimensions: [u32; 2]) -> [f32; 2] {
+        let ndc = [
+            pixel[0] / dimensions[0] as f32 * 2.0 - 1.0,
+            pixel[1] / dimensions[1] as f32 * 2.0 - 1.0,
+        ];
+        [
+            ndc[0] / (self.zoom * self.aspect) + self.center[0],
+            ndc[1] / self.zoom + self.center[1],
+        ]
+    }
+
+    /// The pixel `world` appears at, the inverse of `screen_to_world`.
+    pub fn world_to_screen(&self, world: [f32; 2], dimensions: [u32; 2]) -> [f32; 2] {
+        let ndc = [
+            (world[0] - self.center[0]) * self.zoom * self.aspect,
+            (world[1] - self.center[1]) * self.zoom,
+        ];
+        [
+            (ndc[0] + 1.0) * 0.5 * dimensions[0] as f32,
+            (ndc[1] + 1.0) * 0.5 * dimensions[1] as f32,
+        ]
+    }
+
+    /// Moves the view so the world position under `from` ends up under `to`.
+    pub fn pan(&mut self, from: [f32; 2], to: [f32; 2], dimensions: [u32; 2]) {
+        let from = self.screen_to_world(from, dimensions);
+        let to = self.screen_to_world(to, dimensions);
+        self.center[0] += from[0] - to[0];
+        self.center[1] += from[1] - to[1];
+    }
+
+    /// Multiplies the zoom by `factor` while keeping the world position under `pixel` in place.
+    pub fn zoom_at(&mut self, pixel: [f32; 2], factor: f32, dimensions: [u32; 2]) {
+        let anchor = self.screen_to_world(pixel, dimensions);
+        self.zoom *= factor;
+        let moved = self.screen_to_world(pixel, dimensions);
+        self.center[0] += anchor[0] - moved[0];
+        self.center[1] += anchor[1] - moved[1];
+    }
+}
@@ -1,63 +1,179 @@
-use image::{ImageBuffer, Rgba};
+use crate::examples::export::ImageOutput;
+use crate::examples::readback::{Pixels, ReadbackBuffer};
+use std::path::Path;
 use std::sync::Arc;
-use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer};
 use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBuffer};
 use vulkano::descriptor::descriptor_set::PersistentDescriptorSet;
 use vulkano::descriptor::PipelineLayoutAbstract;
 use vulkano::device::{Device, Queue};
 use vulkano::format::Format;
 use vulkano::image::{Dimensions, StorageImage};
-use vulkano::pipeline::ComputePipeline;
+use vulkano::pipeline::{ComputePipeline, ComputePipelineAbstract};
 use vulkano::sync::GpuFuture;
 
-pub fn compute_mandel_and_save(device: Arc<Device>, queue: Arc<Queue>) {
+/// Where the sub-pixel samples are placed inside each pixel.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SamplePattern {
+    Grid = 0,
+    Jittered = 1,
+    RotatedGrid = 2,
+}
+
+/// How the samples are combined into the final pixel.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DownsampleFilter {
+    Box = 0,
+    Lanczos = 1,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Supersampling {
+    /// Samples per pixel along each axis, so `grid_size * grid_size` samples per pixel.
+    pub grid_size: u32,
+    pub pattern: SamplePattern,
+    pub filter: DownsampleFilter,
+    /// When set, only pixels whose neighbours differ by more than this are supersampled.
+    pub adaptive_threshold: Option<f32>,
+}
+
+impl Supersampling {
+    pub fn none() -> Supersampling {
+        Supersampling {
+            grid_size: 1,
+            pattern: SamplePattern::Grid,
+            filter: DownsampleFilter::Box,
+            adaptive_threshold: None,
+        }
+    }
+}
+
+pub fn compute_mandel_and_save<P: AsRef<Path>>(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    supersampling: Supersampling,
+    output: ImageOutput,
+    path: P,
+) {
+    compute_mandel(device, queue, supersampling, output.storage_format())
+        .save(path)
+        .unwrap();
+}
+
+/// Renders the Mandelbrot set into a storage image of `format`, which must be one of
+/// `R8G8B8A8Unorm`, `R16G16B16A16Unorm`, `R16G16B16A16Sfloat` or `R32Sfloat` (raw iteration
+/// counts).
+pub fn compute_mandel(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    supersampling: Supersampling,
+    format: Format,
+) -> Pixels {
     let img_mandel = StorageImage::new(
         device.clone(),
         Dimensions::Dim2d {
             width: 1024,
             height: 1024,
         },
-        Format::R8G8B8A8Unorm,
+        format,
         Some(queue.family()),
     )
     .unwrap();
 
-    let mandel_buff = CpuAccessibleBuffer::from_iter(
-        device.clone(),
-        BufferUsage::all(),
-        false,
-        (0..1024 * 1024 * 4).map(|_| 0u8),
-    )
-    .expect("failed to create mandel_buff");
-
-    let shader_mandel =
-        cs_mandel::Shader::load(device.clone()).expect("failed to create shader module");
-
-    let mandel_pipeline = Arc::new(
-        ComputePipeline::new(device.clone(), &shader_mandel.main_entry_point(), &())
-            .expect("failed to create multiplypline"),
-    );
-
-    let mandel_layout = mandel_pipeline.layout().descriptor_set_layout(0).unwrap();
-    let mandel_set = Arc::new(
-        PersistentDescriptorSet::start(mandel_layout.clone())
-            .add_image(img_mandel.clone())
-            .unwrap()
-            .build()
-            .unwrap(),
-    );
-
-    let mandel_cmd_buffer = AutoCommandBufferBuilder::new(device.clone(), queue.family())
-        .unwrap()
-        .dispatch(
-            [1024 / 8, 1024 / 8, 1],
-            mandel_pipeline.clone(),
-            mandel_set.clone(),
-            (),
-        )
-        .unwrap()
-        .copy_image_to_buffer(img_mandel.clone(), mandel_buff.clone())
-        .unwrap()
+    let readback = ReadbackBuffer::new(device.clone(), [1024, 1024], format);
+
+    let (mandel_pipeline, refine_pipeline) = mandel_pipelines(device.clone(), format);
+
+    let builder = AutoCommandBufferBuilder::new(device.clone(), queue.family()).unwrap();
+
+    let builder = match supersampling.adaptive_threshold {
+        None => {
+            let mandel_layout = mandel_pipeline.layout().descriptor_set_layout(0).unwrap();
+            let mandel_set = Arc::new(
+                PersistentDescriptorSet::start(mandel_layout.clone())
+                    .add_image(img_mandel.clone())
+                    .unwrap()
+                    .build()
+                    .unwrap(),
+            );
+
+            builder
+                .dispatch(
+                    [1024 / 8, 1024 / 8, 1],
+                    mandel_pipeline.clone(),
+                    mandel_set.clone(),
+                    cs_mandel::ty::PushConstants {
+                        grid_size: supersampling.grid_size,
+                        pattern: supersampling.pattern as u32,
+                        filter_kind: supersampling.filter as u32,
+                        threshold: 0.0,
+                    },
+                )
+                .unwrap()
+        }
+        Some(threshold) => {
+            // Render a single sample per pixel first, then let the refine pass supersample
+            // only where that coarse image has edges.
+            let img_coarse = StorageImage::new(
+                device.clone(),
+                Dimensions::Dim2d {
+                    width: 1024,
+                    height: 1024,
+                },
+                format,
+                Some(queue.family()),
+            )
+            .unwrap();
+
+            let coarse_layout = mandel_pipeline.layout().descriptor_set_layout(0).unwrap();
+            let coarse_set = Arc::new(
+                PersistentDescriptorSet::start(coarse_layout.clone())
+                    .add_image(img_coarse.clone())
+                    .unwrap()
+                    .build()
+                    .unwrap(),
+            );
+
+            let refine_layout = refine_pipeline.layout().descriptor_set_layout(0).unwrap();
+            let refine_set = Arc::new(
+                PersistentDescriptorSet::start(refine_layout.clone())
+                    .add_image(img_mandel.clone())
+                    .unwrap()
+                    .add_image(img_coarse.clone())
+                    .unwrap()
+                    .build()
+                    .unwrap(),
+            );
+
+            builder
+                .dispatch(
+                    [1024 / 8, 1024 / 8, 1],
+                    mandel_pipeline.clone(),
+                    coarse_set.clone(),
+                    cs_mandel::ty::PushConstants {
+                        grid_size: 1,
+                        pattern: SamplePattern::Grid as u32,
+                        filter_kind: DownsampleFilter::Box as u32,
+                        threshold: 0.0,
+                    },
+                )
+                .unwrap()
+                .dispatch(
+                    [1024 / 8, 1024 / 8, 1],
+                    refine_pipeline.clone(),
+                    refine_set.clone(),
+                    cs_mandel::ty::PushConstants {
+                        grid_size: supersampling.grid_size,
+                        pattern: supersampling.pattern as u32,
+                        filter_kind: supersampling.filter as u32,
+                        threshold,
+                    },
+                )
+                .unwrap()
+        }
+    };
+
+    let mandel_cmd_buffer = readback
+        .copy_from(builder, img_mandel.clone())
         .build()
         .unwrap();
 
@@ -68,10 +184,51 @@ pub fn compute_mandel_and_save(device: Arc<Device>, queue: Arc<Queue>) {
         .wait(None)
         .unwrap();
 
-    let mandel_data = mandel_buff.read().unwrap();
-    let img_mandel = ImageBuffer::<Rgba<u8>, _>::from_raw(1024, 1024, &mandel_data[..]).unwrap();
+    readback.pixels()
+}
+
+type DynComputePipeline = Arc<dyn ComputePipelineAbstract + Send + Sync>;
+
+/// The full and adaptive refine pipelines writing to a storage image of `format`.
+fn mandel_pipelines(
+    device: Arc<Device>,
+    format: Format,
+) -> (DynComputePipeline, DynComputePipeline) {
+    macro_rules! pipeline {
+        ($module:ident) => {{
+            let shader =
+                $module::Shader::load(device.clone()).expect("failed to create shader module");
+            Arc::new(
+                ComputePipeline::new(device.clone(), &shader.main_entry_point(), &())
+                    .expect("failed to create mandel pipeline"),
+            ) as DynComputePipeline
+        }};
+    }
 
-    img_mandel.save("mandel.png").unwrap();
+    // All variants share the push constant block, so `cs_mandel::ty::PushConstants` fits each.
+    match format {
+        Format::R8G8B8A8Unorm => (pipeline!(cs_mandel), pipeline!(cs_mandel_refine)),
+        Format::R16G16B16A16Unorm => {
+            assert!(
+                device
+                    .enabled_features()
+                    .shader_storage_image_extended_formats,
+                "writing {:?} from a shader needs shaderStorageImageExtendedFormats, which this \
+                 device doesn't support",
+                format
+            );
+            (
+                pipeline!(cs_mandel_rgba16),
+                pipeline!(cs_mandel_refine_rgba16),
+            )
+        }
+        Format::R16G16B16A16Sfloat => (
+            pipeline!(cs_mandel_rgba16f),
+            pipeline!(cs_mandel_refine_rgba16f),
+        ),
+        Format::R32Sfloat => (pipeline!(cs_mandel_r32f), pipeline!(cs_mandel_refine_r32f)),
+        _ => panic!("no mandelbrot shader writes to {:?}", format),
+    }
 }
 
 mod cs_mandel {
@@ -80,3 +237,52 @@ mod cs_mandel {
         path: "shaders/mandelbrot.comp.glsl"
     }
 }
+
+mod cs_mandel_refine {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/mandelbrot_refine.comp.glsl"
+    }
+}
+
+mod cs_mandel_rgba16 {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/mandelbrot_rgba16.comp.glsl"
+    }
+}
+
+mod cs_mandel_refine_rgba16 {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/mandelbrot_refine_rgba16.comp.glsl"
+    }
+}
+
+mod cs_mandel_rgba16f {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/mandelbrot_rgba16f.comp.glsl"
+    }
+}
+
+mod cs_mandel_refine_rgba16f {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/mandelbrot_refine_rgba16f.comp.glsl"
+    }
+}
+
+mod cs_mandel_r32f {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/mandelbrot_r32f.comp.glsl"
+    }
+}
+
+mod cs_mandel_refine_r32f {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/mandelbrot_refine_r32f.comp.glsl"
+    }
+}
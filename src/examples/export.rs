@@ -0,0 +1,179 @@
+use image::codecs::hdr::HdrEncoder;
+use image::Rgb;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use vulkano::format::Format;
+
+/// File types the offscreen examples can write their results to.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ImageOutput {
+    /// 8 bit per channel PNG, rendered into `R8G8B8A8Unorm`.
+    Png8,
+    /// 16 bit per channel PNG, rendered into `R16G16B16A16Unorm` so every bit of the PNG is
+    /// rendered, a half float only has 11 bits of precision in `[0, 1]`.
+    Png16,
+    /// Radiance HDR, rendered into `R16G16B16A16Sfloat`.
+    Hdr,
+    /// OpenEXR with 32 bit float channels, rendered into `R16G16B16A16Sfloat`.
+    Exr,
+    /// Single channel numpy array, rendered into `R32Sfloat`.
+    Npy,
+}
+
+impl ImageOutput {
+    /// The storage image format the GPU should render into for this output.
+    pub fn storage_format(self) -> Format {
+        match self {
+            ImageOutput::Png8 => Format::R8G8B8A8Unorm,
+            ImageOutput::Png16 => Format::R16G16B16A16Unorm,
+            ImageOutput::Hdr | ImageOutput::Exr => Format::R16G16B16A16Sfloat,
+            ImageOutput::Npy => Format::R32Sfloat,
+        }
+    }
+
+    /// The output matching the extension of `path`. PNGs are written with 8 bits per channel,
+    /// unless the file name ends in `_16.png`, e.g. `mandel_16.png`.
+    pub fn for_path<P: AsRef<Path>>(path: P) -> ImageOutput {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        let wide = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map_or(false, |s| s.ends_with("_16"));
+
+        match extension.as_ref().map(|e| e.as_str()) {
+            Some("hdr") => ImageOutput::Hdr,
+            Some("exr") => ImageOutput::Exr,
+            Some("npy") => ImageOutput::Npy,
+            _ if wide => ImageOutput::Png16,
+            _ => ImageOutput::Png8,
+        }
+    }
+}
+
+/// Converts an IEEE 754 half precision float to `f32`.
+pub fn f16_to_f32(bits: u16) -> f32 {
+    let sign = ((bits >> 15) as u32) << 31;
+    let exponent = ((bits >> 10) & 0x1f) as u32;
+    let mantissa = (bits & 0x3ff) as u32;
+
+    let bits = match (exponent, mantissa) {
+        (0, 0) => sign,
+        (0, _) => {
+            // Subnormal half, renormalize into a normal f32.
+            let shift = mantissa.leading_zeros() - 21;
+            sign | ((113 - shift) << 23) | ((mantissa << shift) & 0x3ff) << 13
+        }
+        (0x1f, _) => sign | 0x7f80_0000 | (mantissa << 13),
+        _ => sign | ((exponent + 112) << 23) | (mantissa << 13),
+    };
+
+    f32::from_bits(bits)
+}
+
+/// Writes a Radiance HDR file, dropping the alpha channel.
+pub fn save_hdr<P: AsRef<Path>>(
+    path: P,
+    width: u32,
+    height: u32,
+    rgba: &[f32],
+) -> Result<(), Box<dyn Error>> {
+    let pixels = rgba
+        .chunks_exact(4)
+        .map(|p| Rgb([p[0], p[1], p[2]]))
+        .collect::<Vec<_>>();
+
+    let file = BufWriter::new(File::create(path)?);
+    HdrEncoder::new(file).encode(&pixels, width as usize, height as usize)?;
+
+    Ok(())
+}
+
+pub fn save_exr<P: AsRef<Path>>(
+    path: P,
+    width: u32,
+    height: u32,
+    rgba: &[f32],
+) -> Result<(), Box<dyn Error>> {
+    let width = width as usize;
+
+    exr::prelude::write_rgba_file(path, width, height as usize, |x, y| {
+        let p = &rgba[(y * width + x) * 4..][..4];
+        (p[0], p[1], p[2], p[3])
+    })?;
+
+    Ok(())
+}
+
+/// Writes a row major `height x width` little endian `f32` array in numpy's `.npy` format.
+pub fn save_npy<P: AsRef<Path>>(
+    path: P,
+    width: u32,
+    height: u32,
+    values: &[f32],
+) -> Result<(), Box<dyn Error>> {
+    let count = (width * height) as usize;
+    if values.len() < count {
+        return Err("texel data too small for image".into());
+    }
+
+    let mut header = format!(
+        "{{'descr': '<f4', 'fortran_order': False, 'shape': ({}, {}), }}",
+        height, width
+    );
+    // Magic (6) + version (2) + header length (2) + header + '\n' must be a multiple of 64.
+    let unpadded = 10 + header.len() + 1;
+    header.push_str(&" ".repeat((64 - unpadded % 64) % 64));
+    header.push('\n');
+
+    let mut file = BufWriter::new(File::create(path)?);
+    file.write_all(b"\x93NUMPY\x01\x00")?;
+    file.write_all(&(header.len() as u16).to_le_bytes())?;
+    file.write_all(header.as_bytes())?;
+    for value in &values[..count] {
+        file.write_all(&value.to_le_bytes())?;
+    }
+    file.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::f16_to_f32;
+
+    #[test]
+    fn f16_zeros_keep_their_sign() {
+        assert_eq!(f16_to_f32(0x0000).to_bits(), 0.0f32.to_bits());
+        assert_eq!(f16_to_f32(0x8000).to_bits(), (-0.0f32).to_bits());
+    }
+
+    #[test]
+    fn f16_normals() {
+        assert_eq!(f16_to_f32(0x3c00), 1.0);
+        assert_eq!(f16_to_f32(0xc000), -2.0);
+        assert_eq!(f16_to_f32(0x0400), 2f32.powi(-14));
+        assert_eq!(f16_to_f32(0x7bff), 65504.0);
+    }
+
+    #[test]
+    fn f16_subnormals() {
+        assert_eq!(f16_to_f32(0x0001), 2f32.powi(-24));
+        assert_eq!(f16_to_f32(0x8001), -(2f32.powi(-24)));
+        assert_eq!(f16_to_f32(0x0200), 2f32.powi(-15));
+        assert_eq!(f16_to_f32(0x03ff), 1023.0 * 2f32.powi(-24));
+    }
+
+    #[test]
+    fn f16_infinities_and_nan() {
+        assert_eq!(f16_to_f32(0x7c00), f32::INFINITY);
+        assert_eq!(f16_to_f32(0xfc00), f32::NEG_INFINITY);
+        assert!(f16_to_f32(0x7e00).is_nan());
+        assert!(f16_to_f32(0x7c01).is_nan());
+    }
+}
@@ -0,0 +1,202 @@
+use crate::examples::readback::ReadbackBuffer;
+use std::sync::Arc;
+use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBuffer};
+use vulkano::device::{Device, Queue};
+use vulkano::format::{ClearValue, Format};
+use vulkano::image::{Dimensions, ImageUsage, StorageImage};
+use vulkano::sync::GpuFuture;
+
+const SIZE: u32 = 16;
+
+const FLOAT_CLEAR: [f32; 4] = [0.25, 0.5, 0.75, 1.0];
+const SNORM_CLEAR: [f32; 4] = [-0.5, 0.25, 0.75, -1.0];
+const UINT_CLEAR: [u32; 4] = [3, 7, 11, 1];
+const INT_CLEAR: [i32; 4] = [-3, 7, -11, 1];
+
+/// Which clear value a format is tested with.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Clear {
+    Float,
+    Snorm,
+    Uint,
+    Int,
+}
+
+impl Clear {
+    fn value(self) -> ClearValue {
+        match self {
+            Clear::Float => ClearValue::Float(FLOAT_CLEAR),
+            Clear::Snorm => ClearValue::Float(SNORM_CLEAR),
+            Clear::Uint => ClearValue::Uint(UINT_CLEAR),
+            Clear::Int => ClearValue::Int(INT_CLEAR),
+        }
+    }
+
+    fn expected(self) -> [f32; 4] {
+        match self {
+            Clear::Float => FLOAT_CLEAR,
+            Clear::Snorm => SNORM_CLEAR,
+            Clear::Uint => {
+                let c = UINT_CLEAR;
+                [c[0] as f32, c[1] as f32, c[2] as f32, c[3] as f32]
+            }
+            Clear::Int => {
+                let c = INT_CLEAR;
+                [c[0] as f32, c[1] as f32, c[2] as f32, c[3] as f32]
+            }
+        }
+    }
+}
+
+/// Format, clear value, number of channels stored and the allowed error per channel.
+const MATRIX: &[(Format, Clear, usize, f32)] = &[
+    (Format::R8Unorm, Clear::Float, 1, 1.0 / 255.0),
+    (Format::R8G8B8A8Unorm, Clear::Float, 4, 1.0 / 255.0),
+    (Format::B8G8R8A8Unorm, Clear::Float, 4, 1.0 / 255.0),
+    (Format::R16G16B16A16Unorm, Clear::Float, 4, 1.0 / 65535.0),
+    (Format::R8G8B8A8Srgb, Clear::Float, 4, 0.01),
+    (Format::B8G8R8A8Srgb, Clear::Float, 4, 0.01),
+    (Format::R8Snorm, Clear::Snorm, 1, 1.0 / 127.0),
+    (Format::R8G8B8A8Snorm, Clear::Snorm, 4, 1.0 / 127.0),
+    (Format::R16G16B16A16Snorm, Clear::Snorm, 4, 1.0 / 32767.0),
+    (Format::R8Uint, Clear::Uint, 1, 0.0),
+    (Format::R8G8B8A8Uint, Clear::Uint, 4, 0.0),
+    (Format::R16G16B16A16Uint, Clear::Uint, 4, 0.0),
+    (Format::R32Uint, Clear::Uint, 1, 0.0),
+    (Format::R8G8B8A8Sint, Clear::Int, 4, 0.0),
+    (Format::R16G16B16A16Sint, Clear::Int, 4, 0.0),
+    (Format::R32Sint, Clear::Int, 1, 0.0),
+    (Format::R16Sfloat, Clear::Float, 1, 1e-3),
+    (Format::R16G16B16A16Sfloat, Clear::Float, 4, 1e-3),
+    (Format::R32Sfloat, Clear::Float, 1, 0.0),
+    (Format::R32G32B32A32Sfloat, Clear::Float, 4, 0.0),
+    (Format::R5G6B5UnormPack16, Clear::Float, 3, 1.0 / 31.0),
+    (Format::R4G4B4A4UnormPack16, Clear::Float, 4, 1.0 / 15.0),
+    (Format::A1R5G5B5UnormPack16, Clear::Float, 4, 1.0 / 31.0),
+    (
+        Format::A2B10G10R10UnormPack32,
+        Clear::Float,
+        4,
+        1.0 / 1023.0,
+    ),
+    (Format::A2B10G10R10UintPack32, Clear::Uint, 4, 0.0),
+    (Format::B10G11R11UfloatPack32, Clear::Float, 3, 0.02),
+    (Format::E5B9G9R9UfloatPack32, Clear::Float, 3, 1.0 / 256.0),
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FormatResult {
+    Passed,
+    Failed(String),
+    Skipped(String),
+}
+
+/// Clears an image of every format in the matrix, reads it back and checks the decoded texels
+/// against the clear value. Formats the device can't create for transfers are skipped.
+pub fn format_matrix(device: Arc<Device>, queue: Arc<Queue>) -> Vec<(Format, FormatResult)> {
+    MATRIX
+        .iter()
+        .map(|&(format, clear, channels, tolerance)| {
+            let result = check_format(
+                device.clone(),
+                queue.clone(),
+                format,
+                clear,
+                channels,
+                tolerance,
+            );
+            println!("{:?}: {:?}", format, result);
+            (format, result)
+        })
+        .collect()
+}
+
+/// Runs `format_matrix` and panics if any supported format decodes incorrectly.
+pub fn format_matrix_check(device: Arc<Device>, queue: Arc<Queue>) {
+    let results = format_matrix(device, queue);
+
+    let failed = results
+        .iter()
+        .filter(|(_, result)| matches!(result, FormatResult::Failed(_)))
+        .count();
+    let skipped = results
+        .iter()
+        .filter(|(_, result)| matches!(result, FormatResult::Skipped(_)))
+        .count();
+
+    assert_eq!(failed, 0, "{} formats failed", failed);
+    println!("FORMATS YAY OKAY! ({} skipped)", skipped);
+}
+
+fn check_format(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    format: Format,
+    clear: Clear,
+    channels: usize,
+    tolerance: f32,
+) -> FormatResult {
+    let image = match StorageImage::with_usage(
+        device.clone(),
+        Dimensions::Dim2d {
+            width: SIZE,
+            height: SIZE,
+        },
+        format,
+        ImageUsage {
+            transfer_source: true,
+            transfer_destination: true,
+            ..ImageUsage::none()
+        },
+        Some(queue.family()),
+    ) {
+        Ok(image) => image,
+        Err(e) => return FormatResult::Skipped(format!("{}", e)),
+    };
+
+    let readback = ReadbackBuffer::new(device.clone(), [SIZE, SIZE], format);
+
+    let builder = match AutoCommandBufferBuilder::new(device.clone(), queue.family())
+        .unwrap()
+        .clear_color_image(image.clone(), clear.value())
+    {
+        Ok(builder) => builder,
+        Err(e) => return FormatResult::Skipped(format!("{}", e)),
+    };
+
+    readback
+        .copy_from(builder, image.clone())
+        .build()
+        .unwrap()
+        .execute(queue.clone())
+        .unwrap()
+        .then_signal_fence_and_flush()
+        .unwrap()
+        .wait(None)
+        .unwrap();
+
+    let texels = match readback.pixels().to_rgba_f32() {
+        Ok(texels) => texels,
+        Err(e) => return FormatResult::Failed(format!("{}", e)),
+    };
+
+    // Channels the format doesn't store read back as (0, 0, 0, 1).
+    let mut expected = [0.0, 0.0, 0.0, 1.0];
+    expected[..channels].copy_from_slice(&clear.expected()[..channels]);
+
+    for (i, texel) in texels.iter().enumerate() {
+        let matches = texel
+            .iter()
+            .zip(expected.iter())
+            .all(|(value, expected)| (value - expected).abs() <= tolerance);
+
+        if !matches {
+            return FormatResult::Failed(format!(
+                "texel {} is {:?}, expected {:?}",
+                i, texel, expected
+            ));
+        }
+    }
+
+    FormatResult::Passed
+}
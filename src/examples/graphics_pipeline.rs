@@ -1,16 +1,33 @@
-use image::{ImageBuffer, Rgba};
+use crate::examples::msaa::{
+    clear_values, color_framebuffer, color_render_pass, supported_samples,
+};
+use crate::examples::readback::{Pixels, ReadbackBuffer};
+use std::path::Path;
 use std::sync::Arc;
 use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer};
 use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBuffer, DynamicState};
 use vulkano::device::{Device, Queue};
 use vulkano::format::Format;
-use vulkano::framebuffer::{Framebuffer, Subpass};
+use vulkano::framebuffer::Subpass;
 use vulkano::image::{Dimensions, StorageImage};
 use vulkano::pipeline::viewport::Viewport;
 use vulkano::pipeline::GraphicsPipeline;
 use vulkano::sync::GpuFuture;
 
-pub fn graphics_pipeline(device: Arc<Device>, queue: Arc<Queue>) {
+pub fn graphics_pipeline<P: AsRef<Path>>(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    samples: u32,
+    path: P,
+) {
+    render_triangle(device, queue, samples).save(path).unwrap();
+}
+
+/// Renders the triangle with up to `samples` samples per pixel, resolved into a single sampled
+/// image.
+pub fn render_triangle(device: Arc<Device>, queue: Arc<Queue>, samples: u32) -> Pixels {
+    let samples = supported_samples(device.physical_device(), samples, false);
+
     let vertex1 = Vertex::new(-0.5, -0.5);
     let vertex2 = Vertex::new(0.0, 0.5);
     let vertex3 = Vertex::new(0.5, -0.25);
@@ -23,23 +40,7 @@ pub fn graphics_pipeline(device: Arc<Device>, queue: Arc<Queue>) {
     )
     .unwrap();
 
-    let render_pass = Arc::new(
-        vulkano::single_pass_renderpass!(device.clone(),
-            attachments: {
-                color: {
-                    load: Clear,
-                    store: Store,
-                    format: Format::R8G8B8A8Unorm,
-                    samples: 1,
-                }
-            },
-            pass: {
-                color: [color],
-                depth_stencil: {}
-            }
-        )
-        .unwrap(),
-    );
+    let render_pass = color_render_pass(device.clone(), Format::R8G8B8A8Unorm, samples);
 
     let image = StorageImage::new(
         device.clone(),
@@ -52,20 +53,15 @@ pub fn graphics_pipeline(device: Arc<Device>, queue: Arc<Queue>) {
     )
     .unwrap();
 
-    let dest_buffer = CpuAccessibleBuffer::from_iter(
-        device.clone(),
-        BufferUsage::all(),
-        false,
-        (0..1024 * 1024 * 4).map(|_| 0u8),
-    )
-    .expect("failed to create dest_buffer");
+    let readback = ReadbackBuffer::new(device.clone(), [1024, 1024], Format::R8G8B8A8Unorm);
 
-    let framebuffer = Arc::new(
-        Framebuffer::start(render_pass.clone())
-            .add(image.clone())
-            .unwrap()
-            .build()
-            .unwrap(),
+    let framebuffer = color_framebuffer(
+        device.clone(),
+        render_pass.clone(),
+        image.clone(),
+        [1024, 1024],
+        Format::R8G8B8A8Unorm,
+        samples,
     );
 
     let vert_shader =
@@ -93,29 +89,26 @@ pub fn graphics_pipeline(device: Arc<Device>, queue: Arc<Queue>) {
         ..DynamicState::none()
     };
 
-    let command_buffer =
-        AutoCommandBufferBuilder::primary_one_time_submit(device.clone(), queue.family())
-            .unwrap()
-            .begin_render_pass(
-                framebuffer.clone(),
-                false,
-                vec![[0.0, 0.0, 1.0, 1.0].into()],
-            )
-            .unwrap()
-            .draw(
-                pipeline.clone(),
-                &dynamic_state,
-                vertex_buffer.clone(),
-                (),
-                (),
-            )
-            .unwrap()
-            .end_render_pass()
-            .unwrap()
-            .copy_image_to_buffer(image.clone(), dest_buffer.clone())
-            .unwrap()
-            .build()
-            .unwrap();
+    let builder = AutoCommandBufferBuilder::primary_one_time_submit(device.clone(), queue.family())
+        .unwrap()
+        .begin_render_pass(
+            framebuffer.clone(),
+            false,
+            clear_values(samples, [0.0, 0.0, 1.0, 1.0]),
+        )
+        .unwrap()
+        .draw(
+            pipeline.clone(),
+            &dynamic_state,
+            vertex_buffer.clone(),
+            (),
+            (),
+        )
+        .unwrap()
+        .end_render_pass()
+        .unwrap();
+
+    let command_buffer = readback.copy_from(builder, image.clone()).build().unwrap();
 
     let finished = command_buffer.execute(queue.clone()).unwrap();
     finished
@@ -124,9 +117,7 @@ pub fn graphics_pipeline(device: Arc<Device>, queue: Arc<Queue>) {
         .wait(None)
         .unwrap();
 
-    let buffer_content = dest_buffer.read().unwrap();
-    let image = ImageBuffer::<Rgba<u8>, _>::from_raw(1024, 1024, &buffer_content[..]).unwrap();
-    image.save("triangle.png").unwrap();
+    readback.pixels()
 }
 
 #[derive(Default, Copy, Clone)]
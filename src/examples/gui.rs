@@ -0,0 +1,350 @@
+use std::sync::Arc;
+use std::time::Instant;
+use vulkano::buffer::{BufferUsage, CpuBufferPool};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
+use vulkano::descriptor::descriptor_set::{DescriptorSet, PersistentDescriptorSet};
+use vulkano::descriptor::PipelineLayoutAbstract;
+use vulkano::device::{Device, Queue};
+use vulkano::format::Format;
+use vulkano::framebuffer::{RenderPassAbstract, Subpass};
+use vulkano::image::{Dimensions, ImmutableImage};
+use vulkano::pipeline::blend::{AttachmentBlend, BlendFactor};
+use vulkano::pipeline::viewport::Scissor;
+use vulkano::pipeline::{GraphicsPipeline, GraphicsPipelineAbstract};
+use vulkano::sampler::Sampler;
+use vulkano::swapchain::PresentMode;
+use vulkano::sync::GpuFuture;
+use winit::event::{
+    ElementState, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent,
+};
+
+/// Points a wheel line scrolls by.
+const SCROLL_LINE: f32 = 24.0;
+
+#[derive(Default, Copy, Clone)]
+struct GuiVertex {
+    position: [f32; 2],
+    tex_coord: [f32; 2],
+    /// Premultiplied sRGB.
+    color: [f32; 4],
+}
+
+vulkano::impl_vertex!(GuiVertex, position, tex_coord, color);
+
+/// An egui context drawn with its own pipeline in subpass 0 of the render pass.
+///
+/// Pass it every window event with `input`, build the widgets once per frame with `run` and
+/// record them with `draw` after everything they should cover. One point is one pixel.
+pub struct Gui {
+    context: egui::CtxRef,
+    raw_input: egui::RawInput,
+    pointer: egui::Pos2,
+    start: Instant,
+    queue: Arc<Queue>,
+    pipeline: Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
+    font_set: Option<Arc<dyn DescriptorSet + Send + Sync>>,
+    font_version: u64,
+    vertex_pool: CpuBufferPool<GuiVertex>,
+    index_pool: CpuBufferPool<u32>,
+    meshes: Vec<egui::ClippedMesh>,
+}
+
+impl Gui {
+    pub fn new(
+        device: Arc<Device>,
+        queue: Arc<Queue>,
+        render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
+    ) -> Gui {
+        let vert_shader =
+            vs_gui::Shader::load(device.clone()).expect("failed to create vert_shader");
+        let frag_shader =
+            fs_gui::Shader::load(device.clone()).expect("failed to create frag_shader");
+
+        let pipeline = Arc::new(
+            GraphicsPipeline::start()
+                .vertex_input_single_buffer::<GuiVertex>()
+                .vertex_shader(vert_shader.main_entry_point(), ())
+                .triangle_list()
+                .viewports_scissors_dynamic(1)
+                .fragment_shader(frag_shader.main_entry_point(), ())
+                // egui's colors are premultiplied.
+                .blend_collective(AttachmentBlend {
+                    color_source: BlendFactor::One,
+                    ..AttachmentBlend::alpha_blending()
+                })
+                .render_pass(Subpass::from(render_pass, 0).unwrap())
+                .build(device.clone())
+                .unwrap(),
+        );
+
+        Gui {
+            context: egui::CtxRef::default(),
+            raw_input: egui::RawInput::default(),
+            pointer: egui::Pos2::ZERO,
+            start: Instant::now(),
+            queue,
+            pipeline,
+            font_set: None,
+            font_version: 0,
+            vertex_pool: CpuBufferPool::new(device.clone(), BufferUsage::vertex_buffer()),
+            index_pool: CpuBufferPool::new(device, BufferUsage::index_buffer()),
+            meshes: Vec::new(),
+        }
+    }
+
+    /// Translates `event` into egui input for the next `run`.
+    pub fn input(&mut self, event: &WindowEvent) {
+        let events = &mut self.raw_input.events;
+
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.pointer = egui::pos2(position.x as f32, position.y as f32);
+                events.push(egui::Event::PointerMoved(self.pointer));
+            }
+            WindowEvent::CursorLeft { .. } => events.push(egui::Event::PointerGone),
+            WindowEvent::MouseInput { state, button, .. } => {
+                let button = match button {
+                    MouseButton::Left => egui::PointerButton::Primary,
+                    MouseButton::Right => egui::PointerButton::Secondary,
+                    MouseButton::Middle => egui::PointerButton::Middle,
+                    MouseButton::Other(_) => return,
+                };
+                events.push(egui::Event::PointerButton {
+                    pos: self.pointer,
+                    button,
+                    pressed: *state == ElementState::Pressed,
+                    modifiers: self.raw_input.modifiers,
+                });
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.raw_input.scroll_delta += match delta {
+                    MouseScrollDelta::LineDelta(x, y) => egui::vec2(*x, *y) * SCROLL_LINE,
+                    MouseScrollDelta::PixelDelta(p) => egui::vec2(p.x as f32, p.y as f32),
+                };
+            }
+            WindowEvent::ReceivedCharacter(c) if !c.is_control() => {
+                events.push(egui::Event::Text(c.to_string()));
+            }
+            WindowEvent::ModifiersChanged(state) => {
+                self.raw_input.modifiers = egui::Modifiers {
+                    alt: state.alt(),
+                    ctrl: state.ctrl(),
+                    shift: state.shift(),
+                    mac_cmd: cfg!(target_os = "macos") && state.logo(),
+                    command: if cfg!(target_os = "macos") {
+                        state.logo()
+                    } else {
+                        state.ctrl()
+                    },
+                };
+            }
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state,
+                        virtual_keycode: Some(kc),
+                        ..
+                    },
+                ..
+            } => {
+                if let Some(key) = egui_key(*kc) {
+                    events.push(egui::Event::Key {
+                        key,
+                        pressed: *state == ElementState::Pressed,
+                        modifiers: self.raw_input.modifiers,
+                    });
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Whether the pointer is over a widget or dragging one, so the app should ignore it.
+    pub fn wants_pointer(&self) -> bool {
+        self.context.wants_pointer_input()
+    }
+
+    /// Whether a widget has keyboard focus, so the app should ignore key presses.
+    pub fn wants_keyboard(&self) -> bool {
+        self.context.wants_keyboard_input()
+    }
+
+    /// Lays out the widgets `add_contents` creates for a viewport of `dimensions` pixels.
+    pub fn run<F: FnOnce(&egui::CtxRef)>(&mut self, dimensions: [u32; 2], add_contents: F) {
+        let mut raw_input = std::mem::take(&mut self.raw_input);
+        // Modifiers stay held across frames, unlike the events.
+        self.raw_input.modifiers = raw_input.modifiers;
+        raw_input.screen_rect = Some(egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(dimensions[0] as f32, dimensions[1] as f32),
+        ));
+        raw_input.pixels_per_point = Some(1.0);
+        raw_input.time = Some(self.start.elapsed().as_secs_f64());
+
+        self.context.begin_frame(raw_input);
+        add_contents(&self.context);
+        let (_output, shapes) = self.context.end_frame();
+        self.meshes = self.context.tessellate(shapes);
+
+        self.upload_font();
+    }
+
+    /// Records the widgets of the last `run`.
+    pub fn draw(
+        &mut self,
+        mut builder: AutoCommandBufferBuilder,
+        dynamic_state: &DynamicState,
+        dimensions: [u32; 2],
+    ) -> AutoCommandBufferBuilder {
+        let font_set = match &self.font_set {
+            Some(set) => set.clone(),
+            None => return builder,
+        };
+
+        for egui::ClippedMesh(clip, mesh) in self.meshes.drain(..) {
+            // Only the font texture is ever handed to egui.
+            if mesh.indices.is_empty() || mesh.texture_id != egui::TextureId::Egui {
+                continue;
+            }
+
+            let min = [
+                clip.min.x.max(0.0).round() as u32,
+                clip.min.y.max(0.0).round() as u32,
+            ];
+            let max = [
+                (clip.max.x.round() as u32).min(dimensions[0]),
+                (clip.max.y.round() as u32).min(dimensions[1]),
+            ];
+            if max[0] <= min[0] || max[1] <= min[1] {
+                continue;
+            }
+
+            let clipped_state = DynamicState {
+                viewports: dynamic_state.viewports.clone(),
+                scissors: Some(vec![Scissor {
+                    origin: [min[0] as i32, min[1] as i32],
+                    dimensions: [max[0] - min[0], max[1] - min[1]],
+                }]),
+                ..DynamicState::none()
+            };
+
+            let vertex_buffer = self
+                .vertex_pool
+                .chunk(mesh.vertices.iter().map(|v| {
+                    let [r, g, b, a] = v.color.to_array();
+                    GuiVertex {
+                        position: [v.pos.x, v.pos.y],
+                        tex_coord: [v.uv.x, v.uv.y],
+                        color: [
+                            r as f32 / 255.0,
+                            g as f32 / 255.0,
+                            b as f32 / 255.0,
+                            a as f32 / 255.0,
+                        ],
+                    }
+                }))
+                .expect("failed to allocate gui vertices");
+            let index_buffer = self
+                .index_pool
+                .chunk(mesh.indices.iter().cloned())
+                .expect("failed to allocate gui indices");
+
+            builder = builder
+                .draw_indexed(
+                    self.pipeline.clone(),
+                    &clipped_state,
+                    vertex_buffer,
+                    index_buffer,
+                    font_set.clone(),
+                    vs_gui::ty::Screen {
+                        size: [dimensions[0] as f32, dimensions[1] as f32],
+                    },
+                )
+                .unwrap();
+        }
+
+        builder
+    }
+
+    /// Uploads egui's font atlas whenever it grew new glyphs.
+    fn upload_font(&mut self) {
+        let texture = self.context.texture();
+        if self.font_set.is_some() && texture.version == self.font_version {
+            return;
+        }
+
+        let (image, uploaded) = ImmutableImage::from_iter(
+            texture.pixels.iter().cloned(),
+            Dimensions::Dim2d {
+                width: texture.width as u32,
+                height: texture.height as u32,
+            },
+            Format::R8Unorm,
+            self.queue.clone(),
+        )
+        .unwrap();
+
+        uploaded
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        let device = self.queue.device().clone();
+        let layout = self.pipeline.descriptor_set_layout(0).unwrap();
+        self.font_set = Some(Arc::new(
+            PersistentDescriptorSet::start(layout.clone())
+                .add_sampled_image(image, Sampler::simple_repeat_linear_no_mipmap(device))
+                .unwrap()
+                .build()
+                .unwrap(),
+        ));
+        self.font_version = texture.version;
+    }
+}
+
+/// A row of radio buttons for the present modes the runner can switch to.
+pub fn present_mode_buttons(ui: &mut egui::Ui, present_mode: &mut PresentMode) {
+    ui.horizontal(|ui| {
+        for &mode in &[
+            PresentMode::Fifo,
+            PresentMode::Relaxed,
+            PresentMode::Mailbox,
+            PresentMode::Immediate,
+        ] {
+            ui.radio_value(present_mode, mode, format!("{:?}", mode));
+        }
+    });
+}
+
+fn egui_key(key: VirtualKeyCode) -> Option<egui::Key> {
+    Some(match key {
+        VirtualKeyCode::Left => egui::Key::ArrowLeft,
+        VirtualKeyCode::Right => egui::Key::ArrowRight,
+        VirtualKeyCode::Up => egui::Key::ArrowUp,
+        VirtualKeyCode::Down => egui::Key::ArrowDown,
+        VirtualKeyCode::Escape => egui::Key::Escape,
+        VirtualKeyCode::Tab => egui::Key::Tab,
+        VirtualKeyCode::Back => egui::Key::Backspace,
+        VirtualKeyCode::Return => egui::Key::Enter,
+        VirtualKeyCode::Space => egui::Key::Space,
+        VirtualKeyCode::Delete => egui::Key::Delete,
+        VirtualKeyCode::Home => egui::Key::Home,
+        VirtualKeyCode::End => egui::Key::End,
+        _ => return None,
+    })
+}
+
+mod vs_gui {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        path: "shaders/gui.vert.glsl"
+    }
+}
+
+mod fs_gui {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "shaders/gui.frag.glsl"
+    }
+}
@@ -0,0 +1,375 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use vulkano::buffer::{BufferUsage, CpuBufferPool};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
+use vulkano::descriptor::descriptor_set::{DescriptorSet, PersistentDescriptorSet};
+use vulkano::descriptor::PipelineLayoutAbstract;
+use vulkano::device::{Device, Queue};
+use vulkano::format::Format;
+use vulkano::framebuffer::{RenderPassAbstract, Subpass};
+use vulkano::image::{Dimensions, ImmutableImage};
+use vulkano::pipeline::{GraphicsPipeline, GraphicsPipelineAbstract};
+use vulkano::sampler::{Filter, MipmapMode, Sampler, SamplerAddressMode};
+use vulkano::sync::GpuFuture;
+
+/// Rows of the 5x7 glyphs for `' '` to `'_'`, the leftmost column in bit 4.
+const FONT_5X7: [[u8; 7]; 64] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04],
+    [0x0a, 0x0a, 0x0a, 0x00, 0x00, 0x00, 0x00],
+    [0x0a, 0x0a, 0x1f, 0x0a, 0x1f, 0x0a, 0x0a],
+    [0x04, 0x0f, 0x14, 0x0e, 0x05, 0x1e, 0x04],
+    [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03],
+    [0x0c, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0d],
+    [0x0c, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00],
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+    [0x00, 0x04, 0x15, 0x0e, 0x15, 0x04, 0x00],
+    [0x00, 0x04, 0x04, 0x1f, 0x04, 0x04, 0x00],
+    [0x00, 0x00, 0x00, 0x00, 0x0c, 0x04, 0x08],
+    [0x00, 0x00, 0x00, 0x1f, 0x00, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0c, 0x0c],
+    [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+    [0x0e, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0e],
+    [0x04, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e],
+    [0x0e, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1f],
+    [0x1f, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0e],
+    [0x02, 0x06, 0x0a, 0x12, 0x1f, 0x02, 0x02],
+    [0x1f, 0x10, 0x1e, 0x01, 0x01, 0x11, 0x0e],
+    [0x06, 0x08, 0x10, 0x1e, 0x11, 0x11, 0x0e],
+    [0x1f, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+    [0x0e, 0x11, 0x11, 0x0e, 0x11, 0x11, 0x0e],
+    [0x0e, 0x11, 0x11, 0x0f, 0x01, 0x02, 0x0c],
+    [0x00, 0x0c, 0x0c, 0x00, 0x0c, 0x0c, 0x00],
+    [0x00, 0x0c, 0x0c, 0x00, 0x0c, 0x04, 0x08],
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02],
+    [0x00, 0x00, 0x1f, 0x00, 0x1f, 0x00, 0x00],
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08],
+    [0x0e, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    [0x0e, 0x11, 0x01, 0x0d, 0x15, 0x15, 0x0e],
+    [0x0e, 0x11, 0x11, 0x11, 0x1f, 0x11, 0x11],
+    [0x1e, 0x11, 0x11, 0x1e, 0x11, 0x11, 0x1e],
+    [0x0e, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0e],
+    [0x1c, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1c],
+    [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x1f],
+    [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x10],
+    [0x0e, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0f],
+    [0x11, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11],
+    [0x0e, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0e],
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0c],
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1f],
+    [0x11, 0x1b, 0x15, 0x15, 0x11, 0x11, 0x11],
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+    [0x0e, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e],
+    [0x1e, 0x11, 0x11, 0x1e, 0x10, 0x10, 0x10],
+    [0x0e, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0d],
+    [0x1e, 0x11, 0x11, 0x1e, 0x14, 0x12, 0x11],
+    [0x0f, 0x10, 0x10, 0x0e, 0x01, 0x01, 0x1e],
+    [0x1f, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e],
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0a, 0x04],
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0a],
+    [0x11, 0x11, 0x0a, 0x04, 0x0a, 0x11, 0x11],
+    [0x11, 0x11, 0x11, 0x0a, 0x04, 0x04, 0x04],
+    [0x1f, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1f],
+    [0x0e, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0e],
+    [0x00, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00],
+    [0x0e, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0e],
+    [0x04, 0x0a, 0x11, 0x00, 0x00, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1f],
+];
+
+const GLYPH_WIDTH: u32 = 5;
+const GLYPH_HEIGHT: u32 = 7;
+/// Glyphs sit in the top left of cells one texel larger, so that neighbours never bleed in.
+const CELL_WIDTH: u32 = GLYPH_WIDTH + 1;
+const CELL_HEIGHT: u32 = GLYPH_HEIGHT + 1;
+const ATLAS_COLUMNS: u32 = 16;
+/// Four rows of glyphs and one whose first cell is solid, for untextured rectangles.
+const ATLAS_ROWS: u32 = 5;
+const ATLAS_WIDTH: u32 = ATLAS_COLUMNS * CELL_WIDTH;
+const ATLAS_HEIGHT: u32 = ATLAS_ROWS * CELL_HEIGHT;
+
+/// How many frame times the graph shows.
+const FRAME_TIME_HISTORY: usize = 120;
+
+#[derive(Default, Copy, Clone)]
+struct HudVertex {
+    position: [f32; 2],
+    tex_coord: [f32; 2],
+    color: [f32; 4],
+}
+
+vulkano::impl_vertex!(HudVertex, position, tex_coord, color);
+
+/// A text overlay drawn on top of whatever the render pass already contains.
+///
+/// Queue text and rectangles in pixel coordinates, measured from the top left corner of the
+/// viewport, then `draw` them all with one draw call inside the render pass.
+pub struct Hud {
+    pipeline: Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
+    atlas_set: Arc<dyn DescriptorSet + Send + Sync>,
+    vertex_pool: CpuBufferPool<HudVertex>,
+    vertices: Vec<HudVertex>,
+    frame_times: VecDeque<f32>,
+}
+
+impl Hud {
+    pub fn new(
+        device: Arc<Device>,
+        queue: Arc<Queue>,
+        render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
+    ) -> Hud {
+        let (atlas, atlas_uploaded) = ImmutableImage::from_iter(
+            font_atlas().into_iter(),
+            Dimensions::Dim2d {
+                width: ATLAS_WIDTH,
+                height: ATLAS_HEIGHT,
+            },
+            Format::R8Unorm,
+            queue.clone(),
+        )
+        .unwrap();
+
+        atlas_uploaded
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        let vert_shader =
+            vs_hud::Shader::load(device.clone()).expect("failed to create vert_shader");
+        let frag_shader =
+            fs_hud::Shader::load(device.clone()).expect("failed to create frag_shader");
+
+        let pipeline = Arc::new(
+            GraphicsPipeline::start()
+                .vertex_input_single_buffer::<HudVertex>()
+                .vertex_shader(vert_shader.main_entry_point(), ())
+                .triangle_list()
+                .viewports_dynamic_scissors_irrelevant(1)
+                .fragment_shader(frag_shader.main_entry_point(), ())
+                .blend_alpha_blending()
+                .render_pass(Subpass::from(render_pass, 0).unwrap())
+                .build(device.clone())
+                .unwrap(),
+        );
+
+        let atlas_layout = pipeline.descriptor_set_layout(0).unwrap();
+        let atlas_set = Arc::new(
+            PersistentDescriptorSet::start(atlas_layout.clone())
+                .add_sampled_image(atlas, atlas_sampler(device.clone()))
+                .unwrap()
+                .build()
+                .unwrap(),
+        );
+
+        Hud {
+            pipeline,
+            atlas_set,
+            vertex_pool: CpuBufferPool::new(device, BufferUsage::vertex_buffer()),
+            vertices: Vec::new(),
+            frame_times: VecDeque::with_capacity(FRAME_TIME_HISTORY),
+        }
+    }
+
+    /// Queues `text` with its top left corner at `position`, every font texel `scale` pixels
+    /// wide. Lowercase letters are drawn as uppercase ones and `\n` starts a new line.
+    pub fn text(&mut self, position: [f32; 2], scale: f32, color: [f32; 4], text: &str) {
+        let [mut x, mut y] = position;
+
+        for c in text.chars() {
+            if c == '\n' {
+                x = position[0];
+                y += (CELL_HEIGHT + 2) as f32 * scale;
+                continue;
+            }
+
+            let glyph = glyph_index(c);
+            let cell = [glyph % ATLAS_COLUMNS, glyph / ATLAS_COLUMNS];
+            let tex_min = [
+                (cell[0] * CELL_WIDTH) as f32 / ATLAS_WIDTH as f32,
+                (cell[1] * CELL_HEIGHT) as f32 / ATLAS_HEIGHT as f32,
+            ];
+            let tex_max = [
+                (cell[0] * CELL_WIDTH + GLYPH_WIDTH) as f32 / ATLAS_WIDTH as f32,
+                (cell[1] * CELL_HEIGHT + GLYPH_HEIGHT) as f32 / ATLAS_HEIGHT as f32,
+            ];
+
+            self.quad(
+                [x, y],
+                [
+                    x + GLYPH_WIDTH as f32 * scale,
+                    y + GLYPH_HEIGHT as f32 * scale,
+                ],
+                tex_min,
+                tex_max,
+                color,
+            );
+
+            x += CELL_WIDTH as f32 * scale;
+        }
+    }
+
+    /// Queues a filled rectangle between the corners `min` and `max`.
+    pub fn rect(&mut self, min: [f32; 2], max: [f32; 2], color: [f32; 4]) {
+        // The middle of the solid cell, so that filtering never reaches a glyph.
+        let solid = [
+            (CELL_WIDTH as f32 / 2.0) / ATLAS_WIDTH as f32,
+            ((ATLAS_ROWS - 1) * CELL_HEIGHT + CELL_HEIGHT / 2) as f32 / ATLAS_HEIGHT as f32,
+        ];
+
+        self.quad(min, max, solid, solid, color);
+    }
+
+    /// Adds a frame time in seconds to the history `frame_time_graph` shows.
+    pub fn record_frame_time(&mut self, seconds: f32) {
+        if self.frame_times.len() == FRAME_TIME_HISTORY {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(seconds);
+    }
+
+    /// Queues a bar per recorded frame time on a dark background of `size`, the top of which
+    /// stands for `max_seconds`. Bars slower than 60 FPS are drawn red.
+    pub fn frame_time_graph(&mut self, position: [f32; 2], size: [f32; 2], max_seconds: f32) {
+        let [x, y] = position;
+        let bottom = y + size[1];
+        let bar_width = size[0] / FRAME_TIME_HISTORY as f32;
+
+        self.rect(position, [x + size[0], bottom], [0.0, 0.0, 0.0, 0.5]);
+
+        for i in 0..self.frame_times.len() {
+            let seconds = self.frame_times[i];
+            let height = (seconds / max_seconds).min(1.0) * size[1];
+            let color = if seconds > 1.0 / 60.0 {
+                [1.0, 0.3, 0.2, 0.9]
+            } else {
+                [0.3, 1.0, 0.4, 0.9]
+            };
+            let left = x + i as f32 * bar_width;
+
+            self.rect([left, bottom - height], [left + bar_width, bottom], color);
+        }
+    }
+
+    /// Draws everything queued since the last call into a viewport of `dimensions` pixels.
+    pub fn draw(
+        &mut self,
+        builder: AutoCommandBufferBuilder,
+        dynamic_state: &DynamicState,
+        dimensions: [f32; 2],
+    ) -> AutoCommandBufferBuilder {
+        if self.vertices.is_empty() {
+            return builder;
+        }
+
+        let vertex_buffer = self
+            .vertex_pool
+            .chunk(self.vertices.drain(..))
+            .expect("failed to allocate hud vertices");
+
+        builder
+            .draw(
+                self.pipeline.clone(),
+                dynamic_state,
+                vertex_buffer,
+                self.atlas_set.clone(),
+                vs_hud::ty::Screen { size: dimensions },
+            )
+            .unwrap()
+    }
+
+    fn quad(
+        &mut self,
+        min: [f32; 2],
+        max: [f32; 2],
+        tex_min: [f32; 2],
+        tex_max: [f32; 2],
+        color: [f32; 4],
+    ) {
+        let corner = |x: usize, y: usize| HudVertex {
+            position: [[min[0], max[0]][x], [min[1], max[1]][y]],
+            tex_coord: [[tex_min[0], tex_max[0]][x], [tex_min[1], tex_max[1]][y]],
+            color,
+        };
+
+        self.vertices.extend_from_slice(&[
+            corner(0, 0),
+            corner(1, 0),
+            corner(0, 1),
+            corner(0, 1),
+            corner(1, 0),
+            corner(1, 1),
+        ]);
+    }
+}
+
+/// Keeps the texels of the font sharp at any scale.
+fn atlas_sampler(device: Arc<Device>) -> Arc<Sampler> {
+    Sampler::new(
+        device,
+        Filter::Nearest,
+        Filter::Nearest,
+        MipmapMode::Nearest,
+        SamplerAddressMode::ClampToEdge,
+        SamplerAddressMode::ClampToEdge,
+        SamplerAddressMode::ClampToEdge,
+        0.0,
+        1.0,
+        0.0,
+        0.0,
+    )
+    .expect("failed to create atlas sampler")
+}
+
+/// The atlas cell of `c`, `'?'` for characters the font lacks.
+fn glyph_index(c: char) -> u32 {
+    match c.to_ascii_uppercase() {
+        c @ ' '..='_' => c as u32 - ' ' as u32,
+        _ => '?' as u32 - ' ' as u32,
+    }
+}
+
+/// Coverage of every glyph of `FONT_5X7` in its cell, followed by the solid cell.
+fn font_atlas() -> Vec<u8> {
+    let mut texels = vec![0u8; (ATLAS_WIDTH * ATLAS_HEIGHT) as usize];
+
+    for (glyph, rows) in FONT_5X7.iter().enumerate() {
+        let cell_x = glyph as u32 % ATLAS_COLUMNS * CELL_WIDTH;
+        let cell_y = glyph as u32 / ATLAS_COLUMNS * CELL_HEIGHT;
+
+        for (y, row) in rows.iter().enumerate() {
+            for x in 0..GLYPH_WIDTH {
+                if row & (1 << (GLYPH_WIDTH - 1 - x)) != 0 {
+                    let texel = (cell_y + y as u32) * ATLAS_WIDTH + cell_x + x;
+                    texels[texel as usize] = 255;
+                }
+            }
+        }
+    }
+
+    let solid_y = (ATLAS_ROWS - 1) * CELL_HEIGHT;
+    for y in solid_y..solid_y + CELL_HEIGHT {
+        for x in 0..CELL_WIDTH {
+            texels[(y * ATLAS_WIDTH + x) as usize] = 255;
+        }
+    }
+
+    texels
+}
+
+mod vs_hud {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        path: "shaders/hud.vert.glsl"
+    }
+}
+
+mod fs_hud {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "shaders/hud.frag.glsl"
+    }
+}
@@ -0,0 +1,461 @@
+use crate::examples::readback::{Pixels, ReadbackBuffer};
+use std::sync::Arc;
+use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBuffer};
+use vulkano::device::{Device, Queue};
+use vulkano::format::{ClearValue, Format};
+use vulkano::image::{
+    Dimensions, ImageLayout, ImageUsage, ImmutableImage, MipmapsCount, StorageImage,
+};
+use vulkano::instance::PhysicalDevice;
+use vulkano::sampler::Filter;
+use vulkano::sync::GpuFuture;
+
+/// What the device allows blits of a format to do with optimal tiling.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BlitSupport {
+    pub src: bool,
+    pub dst: bool,
+    pub linear: bool,
+}
+
+impl BlitSupport {
+    pub fn query(physical: PhysicalDevice, format: Format) -> BlitSupport {
+        let features = format.properties(physical).optimal_tiling_features;
+
+        BlitSupport {
+            src: features.blit_src,
+            dst: features.blit_dst,
+            linear: features.sampled_image_filter_linear,
+        }
+    }
+
+    /// `filter` if the format supports it as a blit source, `Nearest` otherwise.
+    pub fn filter(&self, filter: Filter) -> Filter {
+        match filter {
+            Filter::Linear if !self.linear => Filter::Nearest,
+            filter => filter,
+        }
+    }
+}
+
+/// A 2D image together with the size and format it was created with.
+#[derive(Clone)]
+pub struct SizedImage {
+    pub image: Arc<StorageImage<Format>>,
+    pub dimensions: [u32; 2],
+    pub format: Format,
+}
+
+impl SizedImage {
+    pub fn new(device: Arc<Device>, queue: &Queue, dimensions: [u32; 2], format: Format) -> Self {
+        let image = StorageImage::new(
+            device,
+            Dimensions::Dim2d {
+                width: dimensions[0],
+                height: dimensions[1],
+            },
+            format,
+            Some(queue.family()),
+        )
+        .unwrap();
+
+        SizedImage {
+            image,
+            dimensions,
+            format,
+        }
+    }
+
+    fn corner(&self) -> [i32; 3] {
+        [self.dimensions[0] as i32, self.dimensions[1] as i32, 1]
+    }
+}
+
+/// A 2D image with all of its mip levels, halving down to 1x1.
+pub struct MipChain {
+    pub image: Arc<ImmutableImage<Format>>,
+    /// Dimensions of level 0.
+    pub dimensions: [u32; 2],
+    pub format: Format,
+}
+
+impl MipChain {
+    /// Levels `MipmapsCount::Log2` creates, level 0 included.
+    pub fn levels(&self) -> u32 {
+        32 - self.dimensions[0].max(self.dimensions[1]).leading_zeros()
+    }
+
+    pub fn level_dimensions(&self, level: u32) -> [u32; 2] {
+        [
+            (self.dimensions[0] >> level).max(1),
+            (self.dimensions[1] >> level).max(1),
+        ]
+    }
+}
+
+/// Appends a blit of all of `source` into the given region of `destination` to `builder`.
+fn blit_into(
+    builder: AutoCommandBufferBuilder,
+    source: &SizedImage,
+    destination: &SizedImage,
+    destination_top_left: [i32; 3],
+    destination_bottom_right: [i32; 3],
+    filter: Filter,
+) -> AutoCommandBufferBuilder {
+    builder
+        .blit_image(
+            source.image.clone(),
+            [0, 0, 0],
+            source.corner(),
+            0,
+            0,
+            destination.image.clone(),
+            destination_top_left,
+            destination_bottom_right,
+            0,
+            0,
+            1,
+            filter,
+        )
+        .expect("failed to record blit")
+}
+
+fn checked_filter(device: &Device, format: Format, filter: Filter) -> Filter {
+    let support = BlitSupport::query(device.physical_device(), format);
+    assert!(
+        support.src && support.dst,
+        "{:?} can't be blitted on this device",
+        format
+    );
+
+    let checked = support.filter(filter);
+    if checked != filter {
+        println!("{:?} doesn't support linear blits, using nearest", format);
+    }
+    checked
+}
+
+/// Scales `source` to `dimensions` with a blit.
+pub fn resize(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    source: &SizedImage,
+    dimensions: [u32; 2],
+    filter: Filter,
+) -> SizedImage {
+    let format = source.format;
+    let filter = checked_filter(&device, format, filter);
+    let destination = SizedImage::new(device.clone(), &queue, dimensions, format);
+
+    let builder = AutoCommandBufferBuilder::new(device.clone(), queue.family()).unwrap();
+    blit_into(
+        builder,
+        source,
+        &destination,
+        [0, 0, 0],
+        destination.corner(),
+        filter,
+    )
+    .build()
+    .unwrap()
+    .execute(queue.clone())
+    .unwrap()
+    .then_signal_fence_and_flush()
+    .unwrap()
+    .wait(None)
+    .unwrap();
+
+    destination
+}
+
+/// Builds a mipmapped copy of `source`, with level 0 blitted from `source` and every further
+/// level blitted from the one above it.
+///
+/// All levels end up in one image, so the result can be sampled as a mipmapped texture. The
+/// levels are blitted between separate images first and then copied into the mip levels of the
+/// chain. Blitting from one level of the chain into the next would use the same image as source
+/// and destination of one command, which the command buffer builder rejects, and it only tracks
+/// a single layout for all levels of an image anyway.
+pub fn generate_mip_chain(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    source: &SizedImage,
+    filter: Filter,
+) -> MipChain {
+    let format = source.format;
+    let filter = checked_filter(&device, format, filter);
+
+    let (image, initialization) = ImmutableImage::uninitialized(
+        device.clone(),
+        Dimensions::Dim2d {
+            width: source.dimensions[0],
+            height: source.dimensions[1],
+        },
+        format,
+        MipmapsCount::Log2,
+        ImageUsage {
+            transfer_source: true,
+            transfer_destination: true,
+            sampled: true,
+            ..ImageUsage::none()
+        },
+        ImageLayout::ShaderReadOnlyOptimal,
+        Some(queue.family()),
+    )
+    .expect("failed to create mipmapped image");
+    let initialization = Arc::new(initialization);
+    let chain = MipChain {
+        image,
+        dimensions: source.dimensions,
+        format,
+    };
+
+    let levels = (0..chain.levels())
+        .map(|level| {
+            SizedImage::new(
+                device.clone(),
+                &queue,
+                chain.level_dimensions(level),
+                format,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut builder = AutoCommandBufferBuilder::new(device.clone(), queue.family()).unwrap();
+    builder = blit_into(
+        builder,
+        source,
+        &levels[0],
+        [0, 0, 0],
+        levels[0].corner(),
+        filter,
+    );
+    for pair in levels.windows(2) {
+        builder = blit_into(
+            builder,
+            &pair[0],
+            &pair[1],
+            [0, 0, 0],
+            pair[1].corner(),
+            filter,
+        );
+    }
+
+    for (level, level_image) in levels.iter().enumerate() {
+        let [width, height] = level_image.dimensions;
+        builder = builder
+            .copy_image(
+                level_image.image.clone(),
+                [0, 0, 0],
+                0,
+                0,
+                initialization.clone(),
+                [0, 0, 0],
+                0,
+                level as u32,
+                [width, height, 1],
+                1,
+            )
+            .expect("failed to record mip copy");
+    }
+
+    builder
+        .build()
+        .unwrap()
+        .execute(queue.clone())
+        .unwrap()
+        .then_signal_fence_and_flush()
+        .unwrap()
+        .wait(None)
+        .unwrap();
+
+    chain
+}
+
+pub fn read_image(device: Arc<Device>, queue: Arc<Queue>, source: &SizedImage) -> Pixels {
+    let readback = ReadbackBuffer::new(device.clone(), source.dimensions, source.format);
+
+    let builder = AutoCommandBufferBuilder::new(device.clone(), queue.family()).unwrap();
+    readback
+        .copy_from(builder, source.image.clone())
+        .build()
+        .unwrap()
+        .execute(queue.clone())
+        .unwrap()
+        .then_signal_fence_and_flush()
+        .unwrap()
+        .wait(None)
+        .unwrap();
+
+    readback.pixels()
+}
+
+/// Reads back mip `level` of `chain`.
+pub fn read_mip(device: Arc<Device>, queue: Arc<Queue>, chain: &MipChain, level: u32) -> Pixels {
+    let readback = ReadbackBuffer::new(device.clone(), chain.level_dimensions(level), chain.format);
+
+    let builder = AutoCommandBufferBuilder::new(device.clone(), queue.family()).unwrap();
+    readback
+        .copy_level_from(builder, chain.image.clone(), level)
+        .build()
+        .unwrap()
+        .execute(queue.clone())
+        .unwrap()
+        .then_signal_fence_and_flush()
+        .unwrap()
+        .wait(None)
+        .unwrap();
+
+    readback.pixels()
+}
+
+/// Colors of the two kinds of checkerboard tiles.
+const CHECKER_COLORS: [[f32; 4]; 2] = [[0.0, 1.0, 1.0, 1.0], [1.0, 0.0, 0.5, 1.0]];
+
+/// Builds a square checkerboard of `tiles` by `tiles` tiles out of two cleared 1x1 images,
+/// starting with `CHECKER_COLORS[0]` in the top left corner.
+fn checkerboard(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    tiles: u32,
+    tile_size: u32,
+    format: Format,
+) -> SizedImage {
+    let colors = [
+        SizedImage::new(device.clone(), &queue, [1, 1], format),
+        SizedImage::new(device.clone(), &queue, [1, 1], format),
+    ];
+    let checkerboard = SizedImage::new(
+        device.clone(),
+        &queue,
+        [tiles * tile_size, tiles * tile_size],
+        format,
+    );
+
+    let mut builder = AutoCommandBufferBuilder::new(device.clone(), queue.family())
+        .unwrap()
+        .clear_color_image(
+            colors[0].image.clone(),
+            ClearValue::Float(CHECKER_COLORS[0]),
+        )
+        .unwrap()
+        .clear_color_image(
+            colors[1].image.clone(),
+            ClearValue::Float(CHECKER_COLORS[1]),
+        )
+        .unwrap();
+
+    for y in 0..tiles {
+        for x in 0..tiles {
+            let top_left = [(x * tile_size) as i32, (y * tile_size) as i32, 0];
+            let bottom_right = [
+                top_left[0] + tile_size as i32,
+                top_left[1] + tile_size as i32,
+                1,
+            ];
+            builder = blit_into(
+                builder,
+                &colors[((x + y) % 2) as usize],
+                &checkerboard,
+                top_left,
+                bottom_right,
+                Filter::Nearest,
+            );
+        }
+    }
+
+    builder
+        .build()
+        .unwrap()
+        .execute(queue.clone())
+        .unwrap()
+        .then_signal_fence_and_flush()
+        .unwrap()
+        .wait(None)
+        .unwrap();
+
+    checkerboard
+}
+
+/// Builds a checkerboard, then saves linear and nearest resizes of it and every level of its
+/// mip chain as `<stem>_*.png`.
+pub fn image_blit_and_save(device: Arc<Device>, queue: Arc<Queue>, stem: &str) {
+    let checkerboard = checkerboard(device.clone(), queue.clone(), 8, 64, Format::R8G8B8A8Unorm);
+
+    for &(filter, name) in &[(Filter::Linear, "linear"), (Filter::Nearest, "nearest")] {
+        let resized = resize(
+            device.clone(),
+            queue.clone(),
+            &checkerboard,
+            [300, 200],
+            filter,
+        );
+        read_image(device.clone(), queue.clone(), &resized)
+            .save(format!("{}_resize_{}.png", stem, name))
+            .unwrap();
+    }
+
+    let mips = generate_mip_chain(device.clone(), queue.clone(), &checkerboard, Filter::Linear);
+    for level in 0..mips.levels() {
+        read_mip(device.clone(), queue.clone(), &mips, level)
+            .save(format!("{}_mip{}.png", stem, level))
+            .unwrap();
+    }
+
+    println!("BLIT YAY OKAY! {} mip levels", mips.levels());
+}
+
+/// Reads back every level of the mip chain of a 64x64 checkerboard with 8x8 tiles and checks it.
+///
+/// Halving with a linear filter keeps the tiles while they are at least a texel wide, below that
+/// every texel is the average of both colors.
+pub fn mip_chain_check(device: Arc<Device>, queue: Arc<Queue>) {
+    const TILES: u32 = 8;
+    const TILE_SIZE: u32 = 8;
+    const TOLERANCE: f32 = 2.0 / 255.0;
+
+    let checkerboard = checkerboard(
+        device.clone(),
+        queue.clone(),
+        TILES,
+        TILE_SIZE,
+        Format::R8G8B8A8Unorm,
+    );
+    let mips = generate_mip_chain(device.clone(), queue.clone(), &checkerboard, Filter::Linear);
+    assert_eq!(mips.levels(), 7);
+
+    let mean = |i: usize| (CHECKER_COLORS[0][i] + CHECKER_COLORS[1][i]) / 2.0;
+    let average = [mean(0), mean(1), mean(2), mean(3)];
+
+    for level in 0..mips.levels() {
+        let [width, height] = mips.level_dimensions(level);
+        let texels = read_mip(device.clone(), queue.clone(), &mips, level)
+            .to_rgba_f32()
+            .unwrap();
+        assert_eq!(texels.len(), (width * height) as usize);
+
+        let tile_size = TILE_SIZE >> level;
+        for (i, texel) in texels.iter().enumerate() {
+            let (x, y) = (i as u32 % width, i as u32 / width);
+            let expected = if tile_size > 0 {
+                CHECKER_COLORS[((x / tile_size + y / tile_size) % 2) as usize]
+            } else {
+                average
+            };
+
+            for channel in 0..4 {
+                assert!(
+                    (texel[channel] - expected[channel]).abs() <= TOLERANCE,
+                    "mip {} texel ({}, {}) is {:?}, expected {:?}",
+                    level,
+                    x,
+                    y,
+                    texel,
+                    expected
+                );
+            }
+        }
+    }
+
+    println!("MIP CHAIN YAY OKAY! {} levels", mips.levels());
+}
@@ -1,39 +1,45 @@
-use image::{ImageBuffer, Rgba};
+use crate::examples::export::ImageOutput;
+use crate::examples::readback::{Pixels, ReadbackBuffer};
+use std::path::Path;
 use std::sync::Arc;
-use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer};
 use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBuffer};
 use vulkano::device::{Device, Queue};
-use vulkano::format::ClearValue;
-use vulkano::format::Format;
+use vulkano::format::{ClearValue, Format};
 use vulkano::image::{Dimensions, StorageImage};
 use vulkano::sync::GpuFuture;
 
-pub fn image_clear_and_save(device: Arc<Device>, queue: Arc<Queue>) {
+pub fn image_clear_and_save<P: AsRef<Path>>(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    output: ImageOutput,
+    path: P,
+) {
+    image_clear(device, queue, output.storage_format())
+        .save(path)
+        .unwrap();
+}
+
+pub fn image_clear(device: Arc<Device>, queue: Arc<Queue>, format: Format) -> Pixels {
     let image = StorageImage::new(
         device.clone(),
         Dimensions::Dim2d {
             width: 1024,
             height: 1024,
         },
-        Format::R8G8B8A8Unorm,
+        format,
         Some(queue.family()),
     )
     .unwrap();
 
-    let image_dest_buffer = CpuAccessibleBuffer::from_iter(
-        device.clone(),
-        BufferUsage::all(),
-        false,
-        (0..1024 * 1024 * 4).map(|_| 0u8),
-    )
-    .expect("failed to create image_dest_buffer");
+    let readback = ReadbackBuffer::new(device.clone(), [1024, 1024], format);
 
     let image_clear_cmd_buffer = AutoCommandBufferBuilder::new(device.clone(), queue.family())
         .unwrap()
         .clear_color_image(image.clone(), ClearValue::Float([0.0, 1.0, 1.0, 1.0]))
-        .unwrap()
-        .copy_image_to_buffer(image.clone(), image_dest_buffer.clone())
-        .unwrap()
+        .unwrap();
+
+    let image_clear_cmd_buffer = readback
+        .copy_from(image_clear_cmd_buffer, image.clone())
         .build()
         .unwrap();
 
@@ -44,8 +50,5 @@ pub fn image_clear_and_save(device: Arc<Device>, queue: Arc<Queue>) {
         .wait(None)
         .unwrap();
 
-    let image_buffer_data = image_dest_buffer.read().unwrap();
-    let image = ImageBuffer::<Rgba<u8>, _>::from_raw(1024, 1024, &image_buffer_data[..]).unwrap();
-
-    image.save("image.png").unwrap();
+    readback.pixels()
 }
@@ -0,0 +1,562 @@
+use crate::examples::readback::ReadbackBuffer;
+use std::path::Path;
+use std::sync::Arc;
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBuffer};
+use vulkano::descriptor::descriptor_set::PersistentDescriptorSet;
+use vulkano::descriptor::PipelineLayoutAbstract;
+use vulkano::device::{Device, Queue};
+use vulkano::format::Format;
+use vulkano::image::{Dimensions, StorageImage};
+use vulkano::pipeline::ComputePipeline;
+use vulkano::sampler::Sampler;
+use vulkano::sync::GpuFuture;
+
+/// Luminance weights used by `Sobel` and `Threshold`, matching the shaders.
+const LUMINANCE: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+/// Largest difference per channel between the GPU and CPU results, the GPU keeps intermediate
+/// results in half precision.
+const TOLERANCE: f32 = 1.0 / 64.0;
+
+/// Fraction of channels allowed to exceed `TOLERANCE`, a threshold can flip values that are
+/// within rounding distance of its level.
+const OUTLIER_FRACTION: f32 = 0.001;
+
+/// Size of the generated input of `image_filter_check`.
+const CHECK_SIZE: [u32; 2] = [64, 48];
+
+/// A single step of a filter chain. All filters clamp samples to the image edge.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Filter {
+    GaussianBlur {
+        sigma: f32,
+    },
+    BoxBlur {
+        radius: u32,
+    },
+    /// Gradient magnitude of the luminance, written as grey.
+    Sobel,
+    Sharpen {
+        amount: f32,
+    },
+    /// `rows * rgba + offset`, each row computes one output channel.
+    ColorMatrix {
+        rows: [[f32; 4]; 4],
+        offset: [f32; 4],
+    },
+    /// White where the luminance is above `level`, black everywhere else.
+    Threshold {
+        level: f32,
+    },
+}
+
+/// The shader invocations a `Filter` is made of.
+#[derive(Clone, Debug, PartialEq)]
+enum Pass {
+    Separable {
+        direction: [i32; 2],
+        weights: Vec<f32>,
+    },
+    Neighbourhood {
+        kernel: [f32; 9],
+        sobel: bool,
+    },
+    Pointwise {
+        rows: [[f32; 4]; 4],
+        offset: [f32; 4],
+        threshold: Option<f32>,
+    },
+}
+
+impl Filter {
+    fn passes(&self) -> Vec<Pass> {
+        let separable = |weights: Vec<f32>| {
+            vec![
+                Pass::Separable {
+                    direction: [1, 0],
+                    weights: weights.clone(),
+                },
+                Pass::Separable {
+                    direction: [0, 1],
+                    weights,
+                },
+            ]
+        };
+
+        match *self {
+            Filter::GaussianBlur { sigma } => {
+                let radius = (sigma * 3.0).ceil().max(1.0) as i32;
+                let weights = (-radius..=radius)
+                    .map(|i| (-(i * i) as f32 / (2.0 * sigma * sigma)).exp())
+                    .collect::<Vec<_>>();
+                let sum: f32 = weights.iter().sum();
+                separable(weights.iter().map(|w| w / sum).collect())
+            }
+            Filter::BoxBlur { radius } => {
+                let size = radius * 2 + 1;
+                separable((0..size).map(|_| 1.0 / size as f32).collect())
+            }
+            Filter::Sobel => vec![Pass::Neighbourhood {
+                kernel: [0.0; 9],
+                sobel: true,
+            }],
+            Filter::Sharpen { amount } => vec![Pass::Neighbourhood {
+                kernel: [
+                    0.0,
+                    -amount,
+                    0.0,
+                    -amount,
+                    1.0 + 4.0 * amount,
+                    -amount,
+                    0.0,
+                    -amount,
+                    0.0,
+                ],
+                sobel: false,
+            }],
+            Filter::ColorMatrix { rows, offset } => vec![Pass::Pointwise {
+                rows,
+                offset,
+                threshold: None,
+            }],
+            Filter::Threshold { level } => vec![Pass::Pointwise {
+                rows: IDENTITY,
+                offset: [0.0; 4],
+                threshold: Some(level),
+            }],
+        }
+    }
+}
+
+/// Blur, edge detection and threshold, the chain the `filter` example runs.
+pub fn edge_filters() -> [Filter; 3] {
+    [
+        Filter::GaussianBlur { sigma: 2.0 },
+        Filter::Sobel,
+        Filter::Threshold { level: 0.1 },
+    ]
+}
+
+const IDENTITY: [[f32; 4]; 4] = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// Loads `input`, runs `filters` over it on the GPU and saves the result to `output`. The
+/// `filter-check` example compares the GPU filters to the CPU reference.
+pub fn image_filter_and_save<P: AsRef<Path>, Q: AsRef<Path>>(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    input: P,
+    filters: &[Filter],
+    output: Q,
+) {
+    let source = image::open(input)
+        .expect("failed to load input image")
+        .to_rgba8();
+    let (width, height) = source.dimensions();
+
+    let result = filter_on_gpu(device, queue, &source, filters);
+
+    let bytes = result
+        .iter()
+        .flat_map(|p| {
+            p.iter()
+                .map(|v| (v.max(0.0).min(1.0) * 255.0).round() as u8)
+        })
+        .collect::<Vec<_>>();
+    image::RgbaImage::from_raw(width, height, bytes)
+        .unwrap()
+        .save(output)
+        .unwrap();
+
+    println!("FILTER YAY OKAY!");
+}
+
+/// Runs `filters` over a small generated image with gradients, hard edges and a checkerboard,
+/// and panics if the GPU result differs from `filter_on_cpu`.
+pub fn image_filter_check(device: Arc<Device>, queue: Arc<Queue>, filters: &[Filter]) {
+    let [width, height] = CHECK_SIZE;
+    let source = image::RgbaImage::from_fn(width, height, |x, y| {
+        let square = (16..40).contains(&x) && (12..36).contains(&y);
+        let checker = (x / 8 + y / 8) % 2 == 0;
+        image::Rgba([
+            if square { 255 } else { (x * 4) as u8 },
+            (y * 5) as u8,
+            if checker { 200 } else { 30 },
+            255,
+        ])
+    });
+
+    let result = filter_on_gpu(device, queue, &source, filters);
+    assert_matches_cpu(&source, &result, filters);
+
+    println!("FILTER CHECK YAY OKAY!");
+}
+
+/// Panics if more than `OUTLIER_FRACTION` of the channels of `result` differ from the CPU
+/// reference over `source` by more than `TOLERANCE`.
+fn assert_matches_cpu(source: &image::RgbaImage, result: &[[f32; 4]], filters: &[Filter]) {
+    let (width, height) = source.dimensions();
+    let input = source
+        .pixels()
+        .map(|p| {
+            let c = |i: usize| p[i] as f32 / 255.0;
+            [c(0), c(1), c(2), c(3)]
+        })
+        .collect::<Vec<_>>();
+    let reference = filter_on_cpu(&input, width, height, filters);
+
+    let errors = result
+        .iter()
+        .zip(reference.iter())
+        .flat_map(|(a, b)| a.iter().zip(b.iter()).map(|(a, b)| (a - b).abs()))
+        .collect::<Vec<_>>();
+    let max_error = errors.iter().cloned().fold(0.0f32, f32::max);
+    let outliers = errors.iter().filter(|&&e| e > TOLERANCE).count();
+    println!("Max difference to CPU reference: {}", max_error);
+    assert!(
+        outliers as f32 <= errors.len() as f32 * OUTLIER_FRACTION,
+        "{} channels differ from the CPU reference by more than {}",
+        outliers,
+        TOLERANCE
+    );
+}
+
+/// Runs `filters` over `source` on the GPU and returns the RGBA result.
+pub fn filter_on_gpu(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    source: &image::RgbaImage,
+    filters: &[Filter],
+) -> Vec<[f32; 4]> {
+    let (width, height) = source.dimensions();
+    let dimensions = Dimensions::Dim2d { width, height };
+
+    let mut passes = filters.iter().flat_map(Filter::passes).collect::<Vec<_>>();
+    if passes.is_empty() {
+        passes = Filter::ColorMatrix {
+            rows: IDENTITY,
+            offset: [0.0; 4],
+        }
+        .passes();
+    }
+
+    let upload_buffer = CpuAccessibleBuffer::from_iter(
+        device.clone(),
+        BufferUsage::transfer_source(),
+        false,
+        source.as_raw().iter().cloned(),
+    )
+    .expect("failed to create upload_buffer");
+
+    let input = StorageImage::new(
+        device.clone(),
+        dimensions,
+        Format::R8G8B8A8Unorm,
+        Some(queue.family()),
+    )
+    .unwrap();
+
+    // Passes ping-pong between two half float targets.
+    let targets = [
+        StorageImage::new(
+            device.clone(),
+            dimensions,
+            Format::R16G16B16A16Sfloat,
+            Some(queue.family()),
+        )
+        .unwrap(),
+        StorageImage::new(
+            device.clone(),
+            dimensions,
+            Format::R16G16B16A16Sfloat,
+            Some(queue.family()),
+        )
+        .unwrap(),
+    ];
+
+    let readback = ReadbackBuffer::new(device.clone(), [width, height], Format::R16G16B16A16Sfloat);
+
+    let sampler = Sampler::simple_repeat_linear_no_mipmap(device.clone());
+
+    let separable_shader =
+        cs_separable::Shader::load(device.clone()).expect("failed to create shader module");
+    let separable_pipeline = Arc::new(
+        ComputePipeline::new(device.clone(), &separable_shader.main_entry_point(), &())
+            .expect("failed to create separable_pipeline"),
+    );
+
+    let neighbourhood_shader =
+        cs_neighbourhood::Shader::load(device.clone()).expect("failed to create shader module");
+    let neighbourhood_pipeline = Arc::new(
+        ComputePipeline::new(
+            device.clone(),
+            &neighbourhood_shader.main_entry_point(),
+            &(),
+        )
+        .expect("failed to create neighbourhood_pipeline"),
+    );
+
+    let pointwise_shader =
+        cs_pointwise::Shader::load(device.clone()).expect("failed to create shader module");
+    let pointwise_pipeline = Arc::new(
+        ComputePipeline::new(device.clone(), &pointwise_shader.main_entry_point(), &())
+            .expect("failed to create pointwise_pipeline"),
+    );
+
+    let groups = [(width + 7) / 8, (height + 7) / 8, 1];
+
+    let mut builder = AutoCommandBufferBuilder::new(device.clone(), queue.family())
+        .unwrap()
+        .copy_buffer_to_image(upload_buffer.clone(), input.clone())
+        .unwrap();
+
+    for (i, pass) in passes.iter().enumerate() {
+        let src = if i == 0 {
+            input.clone()
+        } else {
+            targets[(i - 1) % 2].clone()
+        };
+        let dst = targets[i % 2].clone();
+
+        builder = match pass {
+            Pass::Separable { direction, weights } => {
+                let weights_buffer = CpuAccessibleBuffer::from_iter(
+                    device.clone(),
+                    BufferUsage::all(),
+                    false,
+                    weights.iter().cloned(),
+                )
+                .expect("failed to create weights_buffer");
+
+                let layout = separable_pipeline
+                    .layout()
+                    .descriptor_set_layout(0)
+                    .unwrap();
+                let set = Arc::new(
+                    PersistentDescriptorSet::start(layout.clone())
+                        .add_sampled_image(src, sampler.clone())
+                        .unwrap()
+                        .add_image(dst)
+                        .unwrap()
+                        .add_buffer(weights_buffer)
+                        .unwrap()
+                        .build()
+                        .unwrap(),
+                );
+
+                builder
+                    .dispatch(
+                        groups,
+                        separable_pipeline.clone(),
+                        set,
+                        cs_separable::ty::PushConstants {
+                            direction: *direction,
+                            radius: (weights.len() / 2) as i32,
+                        },
+                    )
+                    .unwrap()
+            }
+            Pass::Neighbourhood { kernel, sobel } => {
+                let layout = neighbourhood_pipeline
+                    .layout()
+                    .descriptor_set_layout(0)
+                    .unwrap();
+                let set = Arc::new(
+                    PersistentDescriptorSet::start(layout.clone())
+                        .add_sampled_image(src, sampler.clone())
+                        .unwrap()
+                        .add_image(dst)
+                        .unwrap()
+                        .build()
+                        .unwrap(),
+                );
+
+                builder
+                    .dispatch(
+                        groups,
+                        neighbourhood_pipeline.clone(),
+                        set,
+                        cs_neighbourhood::ty::PushConstants {
+                            kernel: *kernel,
+                            sobel: *sobel as u32,
+                        },
+                    )
+                    .unwrap()
+            }
+            Pass::Pointwise {
+                rows,
+                offset,
+                threshold,
+            } => {
+                let layout = pointwise_pipeline
+                    .layout()
+                    .descriptor_set_layout(0)
+                    .unwrap();
+                let set = Arc::new(
+                    PersistentDescriptorSet::start(layout.clone())
+                        .add_sampled_image(src, sampler.clone())
+                        .unwrap()
+                        .add_image(dst)
+                        .unwrap()
+                        .build()
+                        .unwrap(),
+                );
+
+                // GLSL matrices are column major.
+                let mut matrix = [[0.0; 4]; 4];
+                for (r, row) in rows.iter().enumerate() {
+                    for (c, value) in row.iter().enumerate() {
+                        matrix[c][r] = *value;
+                    }
+                }
+
+                builder
+                    .dispatch(
+                        groups,
+                        pointwise_pipeline.clone(),
+                        set,
+                        cs_pointwise::ty::PushConstants {
+                            matrix,
+                            offset: *offset,
+                            threshold: threshold.unwrap_or(0.0),
+                            apply_threshold: threshold.is_some() as u32,
+                        },
+                    )
+                    .unwrap()
+            }
+        };
+    }
+
+    let command_buffer = readback
+        .copy_from(builder, targets[(passes.len() - 1) % 2].clone())
+        .build()
+        .unwrap();
+
+    command_buffer
+        .execute(queue.clone())
+        .unwrap()
+        .then_signal_fence_and_flush()
+        .unwrap()
+        .wait(None)
+        .unwrap();
+
+    readback.pixels().to_rgba_f32().unwrap()
+}
+
+/// CPU reference implementation of `filter_on_gpu`, working on RGBA values in `[0, 1]`.
+pub fn filter_on_cpu(
+    input: &[[f32; 4]],
+    width: u32,
+    height: u32,
+    filters: &[Filter],
+) -> Vec<[f32; 4]> {
+    let (width, height) = (width as i32, height as i32);
+    let at = |image: &[[f32; 4]], x: i32, y: i32| {
+        let x = x.max(0).min(width - 1);
+        let y = y.max(0).min(height - 1);
+        image[(y * width + x) as usize]
+    };
+    let luminance = |p: [f32; 4]| p[0] * LUMINANCE[0] + p[1] * LUMINANCE[1] + p[2] * LUMINANCE[2];
+
+    let mut image = input.to_vec();
+
+    for pass in filters.iter().flat_map(Filter::passes) {
+        let mut output = Vec::with_capacity(image.len());
+
+        for y in 0..height {
+            for x in 0..width {
+                let center = at(&image, x, y);
+
+                let pixel = match &pass {
+                    Pass::Separable { direction, weights } => {
+                        let radius = (weights.len() / 2) as i32;
+                        let mut sum = [0.0; 4];
+                        for (i, weight) in (-radius..=radius).zip(weights.iter()) {
+                            let texel = at(&image, x + direction[0] * i, y + direction[1] * i);
+                            for c in 0..4 {
+                                sum[c] += texel[c] * weight;
+                            }
+                        }
+                        sum
+                    }
+                    Pass::Neighbourhood { kernel, sobel } => {
+                        let mut sum = [0.0; 4];
+                        let mut gradient = [0.0; 2];
+                        for dy in -1..=1 {
+                            for dx in -1..=1 {
+                                let texel = at(&image, x + dx, y + dy);
+                                let weight = kernel[((dy + 1) * 3 + dx + 1) as usize];
+                                for c in 0..4 {
+                                    sum[c] += texel[c] * weight;
+                                }
+
+                                let sobel_weight = if dx == 0 || dy == 0 { 2.0 } else { 1.0 };
+                                gradient[0] += dx as f32 * sobel_weight * luminance(texel);
+                                gradient[1] += dy as f32 * sobel_weight * luminance(texel);
+                            }
+                        }
+
+                        if *sobel {
+                            let magnitude = gradient[0].hypot(gradient[1]);
+                            [magnitude, magnitude, magnitude, center[3]]
+                        } else {
+                            [sum[0], sum[1], sum[2], center[3]]
+                        }
+                    }
+                    Pass::Pointwise {
+                        threshold: Some(level),
+                        ..
+                    } => {
+                        let value = if luminance(center) > *level { 1.0 } else { 0.0 };
+                        [value, value, value, center[3]]
+                    }
+                    Pass::Pointwise {
+                        rows,
+                        offset,
+                        threshold: None,
+                    } => {
+                        let mut result = *offset;
+                        for (r, row) in rows.iter().enumerate() {
+                            for c in 0..4 {
+                                result[r] += row[c] * center[c];
+                            }
+                        }
+                        result
+                    }
+                };
+
+                output.push(pixel);
+            }
+        }
+
+        image = output;
+    }
+
+    image
+}
+
+mod cs_separable {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/filter_separable.comp.glsl"
+    }
+}
+
+mod cs_neighbourhood {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/filter_neighbourhood.comp.glsl"
+    }
+}
+
+mod cs_pointwise {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/filter_pointwise.comp.glsl"
+    }
+}
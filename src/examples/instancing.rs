@@ -0,0 +1,222 @@
+use crate::examples::mesh_renderer::{render_turntable, turntable_window, Turntable};
+use crate::examples::readback::Pixels;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::path::Path;
+use std::sync::Arc;
+use vulkano::buffer::{BufferUsage, ImmutableBuffer};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
+use vulkano::device::{Device, Queue};
+use vulkano::format::Format;
+use vulkano::framebuffer::{RenderPassAbstract, Subpass};
+use vulkano::instance::Instance;
+use vulkano::pipeline::vertex::OneVertexOneInstanceDefinition;
+use vulkano::pipeline::{GraphicsPipeline, GraphicsPipelineAbstract};
+use vulkano::swapchain::PresentMode;
+use vulkano::sync::GpuFuture;
+
+/// Seed of the instance layout, so that offscreen renders are reproducible.
+const SEED: u64 = 0x5eed;
+
+#[derive(Default, Copy, Clone)]
+pub struct ColoredVertex {
+    pub position: [f32; 2],
+    pub color: [f32; 3],
+    pub tex_coord: [f32; 2],
+}
+
+vulkano::impl_vertex!(ColoredVertex, position, color, tex_coord);
+
+/// Placement of one quad, read once per instance.
+#[derive(Default, Copy, Clone)]
+pub struct QuadInstance {
+    pub offset: [f32; 2],
+    pub scale: f32,
+    pub angle: f32,
+    pub tint: [f32; 3],
+}
+
+vulkano::impl_vertex!(QuadInstance, offset, scale, angle, tint);
+
+/// Draws many copies of one indexed quad in a single draw call, the vertex buffer providing its
+/// corners and a second buffer where each instance is placed.
+pub struct InstancedQuads {
+    pipeline: Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
+    vertex_buffer: Arc<ImmutableBuffer<[ColoredVertex]>>,
+    instance_buffer: Arc<ImmutableBuffer<[QuadInstance]>>,
+    index_buffer: Arc<ImmutableBuffer<[u16]>>,
+}
+
+impl InstancedQuads {
+    pub fn new(
+        device: Arc<Device>,
+        queue: Arc<Queue>,
+        render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
+        instances: u32,
+    ) -> InstancedQuads {
+        let corners = [
+            ([-1.0, -1.0], [1.0, 0.2, 0.2], [0.0, 0.0]),
+            ([1.0, -1.0], [0.2, 1.0, 0.2], [1.0, 0.0]),
+            ([-1.0, 1.0], [0.2, 0.2, 1.0], [0.0, 1.0]),
+            ([1.0, 1.0], [1.0, 1.0, 0.2], [1.0, 1.0]),
+        ];
+
+        let (vertex_buffer, vertices_uploaded) = ImmutableBuffer::from_iter(
+            corners
+                .iter()
+                .map(|&(position, color, tex_coord)| ColoredVertex {
+                    position,
+                    color,
+                    tex_coord,
+                }),
+            BufferUsage::vertex_buffer(),
+            queue.clone(),
+        )
+        .unwrap();
+
+        let (index_buffer, indices_uploaded) = ImmutableBuffer::from_iter(
+            [0u16, 1, 2, 2, 1, 3].iter().cloned(),
+            BufferUsage::index_buffer(),
+            queue.clone(),
+        )
+        .unwrap();
+
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let (instance_buffer, instances_uploaded) = ImmutableBuffer::from_iter(
+            (0..instances)
+                .map(|_| QuadInstance {
+                    offset: [rng.gen_range(-1.0, 1.0), rng.gen_range(-1.0, 1.0)],
+                    scale: rng.gen_range(0.005, 0.03),
+                    angle: rng.gen_range(0.0, std::f32::consts::PI),
+                    tint: [
+                        rng.gen_range(0.3, 1.0),
+                        rng.gen_range(0.3, 1.0),
+                        rng.gen_range(0.3, 1.0),
+                    ],
+                })
+                .collect::<Vec<_>>()
+                .into_iter(),
+            BufferUsage::vertex_buffer(),
+            queue.clone(),
+        )
+        .unwrap();
+
+        vertices_uploaded
+            .join(indices_uploaded)
+            .join(instances_uploaded)
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        let vert_shader =
+            vs_instanced::Shader::load(device.clone()).expect("failed to create vert_shader");
+        let frag_shader =
+            fs_instanced::Shader::load(device.clone()).expect("failed to create frag_shader");
+
+        let pipeline = Arc::new(
+            GraphicsPipeline::start()
+                .vertex_input(OneVertexOneInstanceDefinition::<ColoredVertex, QuadInstance>::new())
+                .vertex_shader(vert_shader.main_entry_point(), ())
+                .triangle_list()
+                .viewports_dynamic_scissors_irrelevant(1)
+                .fragment_shader(frag_shader.main_entry_point(), ())
+                .render_pass(Subpass::from(render_pass, 0).unwrap())
+                .build(device.clone())
+                .unwrap(),
+        );
+
+        InstancedQuads {
+            pipeline,
+            vertex_buffer,
+            instance_buffer,
+            index_buffer,
+        }
+    }
+}
+
+impl Turntable for InstancedQuads {
+    /// Draws every quad turned by `angle` on top of its own angle.
+    fn draw(
+        &mut self,
+        builder: AutoCommandBufferBuilder,
+        dynamic_state: &DynamicState,
+        aspect: f32,
+        angle: f32,
+    ) -> AutoCommandBufferBuilder {
+        builder
+            .draw_indexed(
+                self.pipeline.clone(),
+                dynamic_state,
+                (self.vertex_buffer.clone(), self.instance_buffer.clone()),
+                self.index_buffer.clone(),
+                (),
+                vs_instanced::ty::View { angle, aspect },
+            )
+            .unwrap()
+    }
+}
+
+pub fn instanced_quads_and_save<P: AsRef<Path>>(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    instances: u32,
+    samples: u32,
+    path: P,
+) {
+    render_instanced_quads(device, queue, instances, [1024, 1024], samples)
+        .save(path)
+        .unwrap();
+}
+
+/// Renders `instances` quads at seeded random places into an image of `dimensions`.
+pub fn render_instanced_quads(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    instances: u32,
+    dimensions: [u32; 2],
+    samples: u32,
+) -> Pixels {
+    render_turntable(
+        device.clone(),
+        queue.clone(),
+        dimensions,
+        Format::R8G8B8A8Unorm,
+        samples,
+        0.0,
+        |render_pass| InstancedQuads::new(device.clone(), queue.clone(), render_pass, instances),
+    )
+}
+
+/// Shows `instances` spinning quads in a window.
+pub fn instanced_quads_window(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    instance: Arc<Instance>,
+    instances: u32,
+    samples: u32,
+    present_mode: PresentMode,
+) {
+    turntable_window(
+        device.clone(),
+        queue.clone(),
+        instance,
+        samples,
+        present_mode,
+        move |render_pass| InstancedQuads::new(device, queue, render_pass, instances),
+    );
+}
+
+mod vs_instanced {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        path: "shaders/instanced.vert.glsl"
+    }
+}
+
+mod fs_instanced {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "shaders/instanced.frag.glsl"
+    }
+}
@@ -0,0 +1,298 @@
+use crate::examples::app::{run_app, App, AppContext, AppOptions, FrameInfo};
+use crate::examples::gui::{present_mode_buttons, Gui};
+use std::sync::Arc;
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
+use vulkano::device::{Device, Queue};
+use vulkano::framebuffer::Subpass;
+use vulkano::instance::Instance;
+use vulkano::pipeline::{GraphicsPipeline, GraphicsPipelineAbstract};
+use vulkano::swapchain::PresentMode;
+use winit::event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent};
+
+/// Complex units per pixel of the initial view, fits the whole set into 800x600.
+const DEFAULT_SCALE: f32 = 3.5 / 800.0;
+const DEFAULT_CENTER: [f32; 2] = [-0.75, 0.0];
+
+/// Scale factor per wheel line.
+const ZOOM_STEP: f32 = 1.2;
+
+/// What the settings panel edits.
+#[derive(Copy, Clone, Debug)]
+struct MandelSettings {
+    max_iterations: u32,
+    inside_color: [f32; 3],
+    near_color: [f32; 3],
+    far_color: [f32; 3],
+}
+
+impl Default for MandelSettings {
+    fn default() -> MandelSettings {
+        MandelSettings {
+            max_iterations: 256,
+            inside_color: [0.0, 0.0, 0.0],
+            near_color: [1.0, 0.8, 0.3],
+            far_color: [0.05, 0.1, 0.3],
+        }
+    }
+}
+
+/// The Mandelbrot set evaluated per fragment, dragged with the left mouse button and zoomed
+/// towards the cursor with the wheel.
+struct MandelExplorer {
+    pipeline: Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
+    vertex_buffer: Arc<CpuAccessibleBuffer<[Vertex]>>,
+    gui: Gui,
+    settings: MandelSettings,
+    present_mode: PresentMode,
+    requested_present_mode: Option<PresentMode>,
+    dimensions: [u32; 2],
+    center: [f32; 2],
+    /// Complex units per pixel.
+    scale: f32,
+    cursor: [f32; 2],
+    dragging: bool,
+}
+
+impl MandelExplorer {
+    fn new(context: &AppContext) -> MandelExplorer {
+        let device = context.device.clone();
+
+        // One triangle covering the whole viewport.
+        let vertex_buffer = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::vertex_buffer(),
+            false,
+            vec![
+                Vertex::new(-1.0, -1.0),
+                Vertex::new(3.0, -1.0),
+                Vertex::new(-1.0, 3.0),
+            ]
+            .into_iter(),
+        )
+        .unwrap();
+
+        let vert_shader =
+            vs_mandel::Shader::load(device.clone()).expect("failed to create vert_shader");
+        let frag_shader =
+            fs_mandel::Shader::load(device.clone()).expect("failed to create frag_shader");
+
+        let pipeline = Arc::new(
+            GraphicsPipeline::start()
+                .vertex_input_single_buffer::<Vertex>()
+                .vertex_shader(vert_shader.main_entry_point(), ())
+                .viewports_dynamic_scissors_irrelevant(1)
+                .fragment_shader(frag_shader.main_entry_point(), ())
+                .render_pass(Subpass::from(context.render_pass.clone(), 0).unwrap())
+                .build(device.clone())
+                .unwrap(),
+        );
+
+        MandelExplorer {
+            pipeline,
+            vertex_buffer,
+            gui: Gui::new(device, context.queue.clone(), context.render_pass.clone()),
+            settings: MandelSettings::default(),
+            present_mode: PresentMode::Fifo,
+            requested_present_mode: None,
+            dimensions: [1, 1],
+            center: DEFAULT_CENTER,
+            scale: DEFAULT_SCALE,
+            cursor: [0.0, 0.0],
+            dragging: false,
+        }
+    }
+
+    /// The point of the complex plane under `pixel`, with the imaginary axis pointing up.
+    fn to_complex(&self, pixel: [f32; 2]) -> [f32; 2] {
+        [
+            self.center[0] + (pixel[0] - self.dimensions[0] as f32 * 0.5) * self.scale,
+            self.center[1] - (pixel[1] - self.dimensions[1] as f32 * 0.5) * self.scale,
+        ]
+    }
+
+    /// Scales the view by `factor` while keeping the point under the cursor in place.
+    fn zoom(&mut self, factor: f32) {
+        let anchor = self.to_complex(self.cursor);
+        self.scale *= factor;
+        let moved = self.to_complex(self.cursor);
+        self.center[0] += anchor[0] - moved[0];
+        self.center[1] += anchor[1] - moved[1];
+    }
+}
+
+/// The settings panel, returns whether the view should be reset.
+fn settings_window(
+    ctx: &egui::CtxRef,
+    settings: &mut MandelSettings,
+    present_mode: &mut PresentMode,
+    scale: f32,
+) -> bool {
+    let mut reset = false;
+
+    egui::Window::new("Mandelbrot")
+        .default_pos([8.0, 8.0])
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.add(
+                egui::Slider::new(&mut settings.max_iterations, 16..=8192)
+                    .logarithmic(true)
+                    .text("iterations"),
+            );
+            ui.horizontal(|ui| {
+                ui.label("inside");
+                ui.color_edit_button_rgb(&mut settings.inside_color);
+                ui.label("near");
+                ui.color_edit_button_rgb(&mut settings.near_color);
+                ui.label("far");
+                ui.color_edit_button_rgb(&mut settings.far_color);
+            });
+            present_mode_buttons(ui, present_mode);
+            ui.horizontal(|ui| {
+                ui.label(format!("zoom: {:.1}x", DEFAULT_SCALE / scale));
+                reset = ui.button("Reset view").clicked();
+            });
+        });
+
+    reset
+}
+
+impl App for MandelExplorer {
+    fn wants_keyboard(&self) -> bool {
+        self.gui.wants_keyboard()
+    }
+
+    fn input(&mut self, event: &WindowEvent) {
+        self.gui.input(event);
+
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                let cursor = [position.x as f32, position.y as f32];
+                if self.dragging {
+                    self.center[0] -= (cursor[0] - self.cursor[0]) * self.scale;
+                    self.center[1] += (cursor[1] - self.cursor[1]) * self.scale;
+                }
+                self.cursor = cursor;
+            }
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => {
+                self.dragging = *state == ElementState::Pressed && !self.gui.wants_pointer();
+            }
+            WindowEvent::MouseWheel { delta, .. } if !self.gui.wants_pointer() => {
+                let lines = match delta {
+                    MouseScrollDelta::LineDelta(_, dy) => *dy,
+                    MouseScrollDelta::PixelDelta(p) => p.y as f32 / 24.0,
+                };
+                self.zoom(ZOOM_STEP.powf(-lines));
+            }
+            _ => (),
+        }
+    }
+
+    fn update(&mut self, _delta_time: f32) {
+        let settings = &mut self.settings;
+        let mut present_mode = self.present_mode;
+        let scale = self.scale;
+        let mut reset = false;
+        self.gui.run(self.dimensions, |ctx| {
+            reset = settings_window(ctx, settings, &mut present_mode, scale)
+        });
+
+        if present_mode != self.present_mode {
+            self.requested_present_mode = Some(present_mode);
+        }
+        if reset {
+            self.center = DEFAULT_CENTER;
+            self.scale = DEFAULT_SCALE;
+        }
+    }
+
+    fn requested_present_mode(&mut self) -> Option<PresentMode> {
+        self.requested_present_mode.take()
+    }
+
+    fn resize(&mut self, dimensions: [u32; 2]) {
+        self.dimensions = dimensions;
+    }
+
+    fn record(
+        &mut self,
+        builder: AutoCommandBufferBuilder,
+        dynamic_state: &DynamicState,
+        frame: &FrameInfo,
+    ) -> AutoCommandBufferBuilder {
+        self.present_mode = frame.present_mode;
+
+        let color = |[r, g, b]: [f32; 3]| [r, g, b, 1.0];
+        let builder = builder
+            .draw(
+                self.pipeline.clone(),
+                dynamic_state,
+                self.vertex_buffer.clone(),
+                (),
+                fs_mandel::ty::Params {
+                    inside_color: color(self.settings.inside_color),
+                    near_color: color(self.settings.near_color),
+                    far_color: color(self.settings.far_color),
+                    center: self.center,
+                    size: [frame.dimensions[0] as f32, frame.dimensions[1] as f32],
+                    scale: self.scale,
+                    max_iterations: self.settings.max_iterations,
+                },
+            )
+            .unwrap();
+
+        self.gui.draw(builder, dynamic_state, frame.dimensions)
+    }
+}
+
+pub fn mandel_explorer(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    instance: Arc<Instance>,
+    samples: u32,
+    present_mode: PresentMode,
+) {
+    run_app(
+        device,
+        queue,
+        instance,
+        AppOptions {
+            title: "Mandelbrot".to_string(),
+            samples,
+            present_mode,
+            frames_in_flight: 2,
+            depth: false,
+        },
+        MandelExplorer::new,
+    );
+}
+
+#[derive(Default, Copy, Clone)]
+struct Vertex {
+    position: [f32; 2],
+}
+
+impl Vertex {
+    fn new(x: f32, y: f32) -> Vertex {
+        Vertex { position: [x, y] }
+    }
+}
+vulkano::impl_vertex!(Vertex, position);
+
+mod vs_mandel {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        path: "shaders/graphics.vert.glsl"
+    }
+}
+
+mod fs_mandel {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "shaders/mandel_explorer.frag.glsl"
+    }
+}
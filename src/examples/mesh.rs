@@ -0,0 +1,153 @@
+use std::error::Error;
+use std::path::Path;
+
+#[derive(Default, Copy, Clone)]
+pub struct MeshVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+}
+
+vulkano::impl_vertex!(MeshVertex, position, normal);
+
+/// An indexed triangle list.
+pub struct Mesh {
+    pub vertices: Vec<MeshVertex>,
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    /// Loads every model of a Wavefront OBJ file into a single mesh. Models without normals get
+    /// smooth normals averaged from their faces.
+    pub fn load_obj<P: AsRef<Path>>(path: P) -> Result<Mesh, Box<dyn Error>> {
+        let (models, _materials) = tobj::load_obj(
+            path.as_ref(),
+            &tobj::LoadOptions {
+                single_index: true,
+                triangulate: true,
+                ..Default::default()
+            },
+        )?;
+
+        let mut mesh = Mesh {
+            vertices: Vec::new(),
+            indices: Vec::new(),
+        };
+
+        for model in models {
+            let base = mesh.vertices.len() as u32;
+            let positions = &model.mesh.positions;
+            let normals = &model.mesh.normals;
+
+            for i in 0..positions.len() / 3 {
+                mesh.vertices.push(MeshVertex {
+                    position: [positions[i * 3], positions[i * 3 + 1], positions[i * 3 + 2]],
+                    normal: if normals.len() == positions.len() {
+                        [normals[i * 3], normals[i * 3 + 1], normals[i * 3 + 2]]
+                    } else {
+                        [0.0; 3]
+                    },
+                });
+            }
+
+            let first_index = mesh.indices.len();
+            mesh.indices
+                .extend(model.mesh.indices.iter().map(|&index| base + index));
+
+            if normals.len() != positions.len() {
+                mesh.compute_normals(base as usize, first_index);
+            }
+        }
+
+        if mesh.indices.is_empty() {
+            return Err(format!("{} contains no faces", path.as_ref().display()).into());
+        }
+
+        Ok(mesh)
+    }
+
+    /// Replaces the normals of the vertices from `first_vertex` on with `smooth_normals` of the
+    /// triangles from `first_index` on.
+    fn compute_normals(&mut self, first_vertex: usize, first_index: usize) {
+        let positions = self.vertices[first_vertex..]
+            .iter()
+            .map(|vertex| vertex.position)
+            .collect::<Vec<_>>();
+        let indices = self.indices[first_index..]
+            .iter()
+            .map(|&index| index - first_vertex as u32)
+            .collect::<Vec<_>>();
+
+        for (vertex, normal) in self.vertices[first_vertex..]
+            .iter_mut()
+            .zip(smooth_normals(&positions, &indices))
+        {
+            vertex.normal = normal;
+        }
+    }
+
+    /// Center and radius of a sphere enclosing every vertex.
+    pub fn bounding_sphere(&self) -> ([f32; 3], f32) {
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+
+        for vertex in &self.vertices {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(vertex.position[axis]);
+                max[axis] = max[axis].max(vertex.position[axis]);
+            }
+        }
+
+        let center = [
+            (min[0] + max[0]) / 2.0,
+            (min[1] + max[1]) / 2.0,
+            (min[2] + max[2]) / 2.0,
+        ];
+        let radius = self
+            .vertices
+            .iter()
+            .map(|vertex| {
+                (0..3)
+                    .map(|axis| (vertex.position[axis] - center[axis]).powi(2))
+                    .sum::<f32>()
+                    .sqrt()
+            })
+            .fold(0.0, f32::max);
+
+        (center, radius.max(f32::EPSILON))
+    }
+}
+
+/// Normals averaged from the area weighted normals of the faces around each vertex.
+pub fn smooth_normals(positions: &[[f32; 3]], indices: &[u32]) -> Vec<[f32; 3]> {
+    let mut normals = vec![[0.0; 3]; positions.len()];
+
+    for triangle in indices.chunks(3) {
+        let [a, b, c] = [
+            positions[triangle[0] as usize],
+            positions[triangle[1] as usize],
+            positions[triangle[2] as usize],
+        ];
+        let ab = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+        let ac = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+        let normal = [
+            ab[1] * ac[2] - ab[2] * ac[1],
+            ab[2] * ac[0] - ab[0] * ac[2],
+            ab[0] * ac[1] - ab[1] * ac[0],
+        ];
+
+        for &index in triangle {
+            for axis in 0..3 {
+                normals[index as usize][axis] += normal[axis];
+            }
+        }
+    }
+
+    for normal in &mut normals {
+        let length = normal.iter().map(|n| n * n).sum::<f32>().sqrt();
+        if length > 0.0 {
+            normal.iter_mut().for_each(|n| *n /= length);
+        }
+    }
+
+    normals
+}
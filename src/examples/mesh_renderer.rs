@@ -0,0 +1,402 @@
+use crate::examples::app::{run_app, App, AppOptions, FrameInfo};
+use crate::examples::mesh::{Mesh, MeshVertex};
+use crate::examples::msaa::{
+    depth_clear_values, depth_framebuffer, depth_render_pass, supported_samples,
+};
+use crate::examples::readback::{Pixels, ReadbackBuffer};
+use cgmath::{Deg, Matrix4, Point3, Rad, Vector3};
+use std::path::Path;
+use std::sync::Arc;
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer, ImmutableBuffer};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBuffer, DynamicState};
+use vulkano::descriptor::descriptor_set::FixedSizeDescriptorSetsPool;
+use vulkano::descriptor::PipelineLayoutAbstract;
+use vulkano::device::{Device, Queue};
+use vulkano::format::Format;
+use vulkano::framebuffer::{RenderPassAbstract, Subpass};
+use vulkano::image::{AttachmentImage, ImageUsage};
+use vulkano::instance::Instance;
+use vulkano::pipeline::viewport::Viewport;
+use vulkano::pipeline::{GraphicsPipeline, GraphicsPipelineAbstract};
+use vulkano::swapchain::PresentMode;
+use vulkano::sync::GpuFuture;
+
+/// Angle the offscreen render turns the mesh by, so that three sides of a cube are visible.
+const OFFSCREEN_ANGLE: f32 = 0.6;
+
+/// Something drawn into subpass 0 of a `depth_render_pass` that can be turned around the
+/// vertical axis, shared by the offscreen and windowed renderers.
+pub trait Turntable {
+    /// Records drawing into the render pass `builder` is in, for a viewport of `aspect` and
+    /// turned by `angle` radians.
+    fn draw(
+        &mut self,
+        builder: AutoCommandBufferBuilder,
+        dynamic_state: &DynamicState,
+        aspect: f32,
+        angle: f32,
+    ) -> AutoCommandBufferBuilder;
+}
+
+/// Draws a mesh with depth testing and Lambert shading into subpass 0 of a `depth_render_pass`.
+pub struct MeshRenderer {
+    device: Arc<Device>,
+    pipeline: Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
+    vertex_buffer: Arc<ImmutableBuffer<[MeshVertex]>>,
+    index_buffer: Arc<ImmutableBuffer<[u32]>>,
+    uniform_pool: FixedSizeDescriptorSetsPool,
+    center: [f32; 3],
+    radius: f32,
+}
+
+impl MeshRenderer {
+    pub fn new(
+        device: Arc<Device>,
+        queue: Arc<Queue>,
+        render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
+        mesh: &Mesh,
+    ) -> MeshRenderer {
+        let (vertex_buffer, vertices_uploaded) = ImmutableBuffer::from_iter(
+            mesh.vertices.iter().cloned(),
+            BufferUsage::vertex_buffer(),
+            queue.clone(),
+        )
+        .unwrap();
+
+        let (index_buffer, indices_uploaded) = ImmutableBuffer::from_iter(
+            mesh.indices.iter().cloned(),
+            BufferUsage::index_buffer(),
+            queue.clone(),
+        )
+        .unwrap();
+
+        vertices_uploaded
+            .join(indices_uploaded)
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        let vert_shader =
+            vs_mesh::Shader::load(device.clone()).expect("failed to create vert_shader");
+        let frag_shader =
+            fs_mesh::Shader::load(device.clone()).expect("failed to create frag_shader");
+
+        let pipeline = Arc::new(
+            GraphicsPipeline::start()
+                .vertex_input_single_buffer::<MeshVertex>()
+                .vertex_shader(vert_shader.main_entry_point(), ())
+                .triangle_list()
+                .viewports_dynamic_scissors_irrelevant(1)
+                .fragment_shader(frag_shader.main_entry_point(), ())
+                .depth_stencil_simple_depth()
+                .render_pass(Subpass::from(render_pass, 0).unwrap())
+                .build(device.clone())
+                .unwrap(),
+        );
+
+        let uniform_layout = pipeline.descriptor_set_layout(0).unwrap();
+        let uniform_pool = FixedSizeDescriptorSetsPool::new(uniform_layout.clone());
+
+        let (center, radius) = mesh.bounding_sphere();
+
+        MeshRenderer {
+            device,
+            pipeline,
+            vertex_buffer,
+            index_buffer,
+            uniform_pool,
+            center,
+            radius,
+        }
+    }
+}
+
+impl Turntable for MeshRenderer {
+    /// Draws the mesh turned around its center, seen by `framing_camera`.
+    fn draw(
+        &mut self,
+        builder: AutoCommandBufferBuilder,
+        dynamic_state: &DynamicState,
+        aspect: f32,
+        angle: f32,
+    ) -> AutoCommandBufferBuilder {
+        let model = turntable(self.center, angle);
+        let (view, projection) = framing_camera(self.radius, aspect);
+
+        let uniform_buffer = CpuAccessibleBuffer::from_data(
+            self.device.clone(),
+            BufferUsage::uniform_buffer(),
+            false,
+            vs_mesh::ty::Transforms {
+                model: model.into(),
+                view: view.into(),
+                projection: projection.into(),
+            },
+        )
+        .expect("failed to create uniform_buffer");
+
+        let uniform_set = self
+            .uniform_pool
+            .next()
+            .add_buffer(uniform_buffer)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        builder
+            .draw_indexed(
+                self.pipeline.clone(),
+                dynamic_state,
+                self.vertex_buffer.clone(),
+                self.index_buffer.clone(),
+                uniform_set,
+                (),
+            )
+            .unwrap()
+    }
+}
+
+/// Turns by `angle` radians around the vertical axis through `center`, which ends up at the
+/// origin.
+pub fn turntable(center: [f32; 3], angle: f32) -> Matrix4<f32> {
+    Matrix4::from_angle_y(Rad(angle)) * Matrix4::from_translation(-Vector3::from(center))
+}
+
+/// View and projection looking at the origin from slightly above, so that a sphere of `radius`
+/// around it fits into view.
+pub fn framing_camera(radius: f32, aspect: f32) -> (Matrix4<f32>, Matrix4<f32>) {
+    let view = Matrix4::look_at(
+        Point3::new(0.0, radius * 1.2, radius * 2.8),
+        Point3::new(0.0, 0.0, 0.0),
+        Vector3::unit_y(),
+    );
+    let projection =
+        vulkan_clip() * cgmath::perspective(Deg(45.0), aspect, radius * 0.1, radius * 10.0);
+
+    (view, projection)
+}
+
+/// Maps OpenGL clip space, as produced by cgmath, to Vulkan's with y pointing down and depth
+/// ranging from 0 to 1.
+pub fn vulkan_clip() -> Matrix4<f32> {
+    Matrix4::new(
+        1.0, 0.0, 0.0, 0.0, //
+        0.0, -1.0, 0.0, 0.0, //
+        0.0, 0.0, 0.5, 0.0, //
+        0.0, 0.0, 0.5, 1.0,
+    )
+}
+
+pub fn mesh_offscreen_and_save<M: AsRef<Path>, P: AsRef<Path>>(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    mesh: M,
+    samples: u32,
+    path: P,
+) {
+    let mesh = Mesh::load_obj(mesh).expect("failed to load mesh");
+    render_mesh(device, queue, &mesh, [1024, 1024], samples)
+        .save(path)
+        .unwrap();
+}
+
+/// Renders `mesh` into an image of `dimensions` with up to `samples` samples per pixel.
+pub fn render_mesh(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    mesh: &Mesh,
+    dimensions: [u32; 2],
+    samples: u32,
+) -> Pixels {
+    render_turntable(
+        device.clone(),
+        queue.clone(),
+        dimensions,
+        Format::R8G8B8A8Unorm,
+        samples,
+        OFFSCREEN_ANGLE,
+        |render_pass| MeshRenderer::new(device.clone(), queue.clone(), render_pass, mesh),
+    )
+}
+
+/// Renders what `renderer` creates for the render pass it is given into an image of
+/// `dimensions` and `format`, turned by `angle` radians.
+pub fn render_turntable<R, F>(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    dimensions: [u32; 2],
+    format: Format,
+    samples: u32,
+    angle: f32,
+    renderer: F,
+) -> Pixels
+where
+    R: Turntable,
+    F: FnOnce(Arc<dyn RenderPassAbstract + Send + Sync>) -> R,
+{
+    let samples = supported_samples(device.physical_device(), samples, true);
+
+    let render_pass = depth_render_pass(device.clone(), format, samples);
+
+    let image = AttachmentImage::with_usage(
+        device.clone(),
+        dimensions,
+        format,
+        ImageUsage {
+            color_attachment: true,
+            transfer_source: true,
+            ..ImageUsage::none()
+        },
+    )
+    .unwrap();
+
+    let readback = ReadbackBuffer::new(device.clone(), dimensions, format);
+
+    let framebuffer = depth_framebuffer(
+        device.clone(),
+        render_pass.clone(),
+        image.clone(),
+        dimensions,
+        format,
+        samples,
+    );
+
+    let mut renderer = renderer(render_pass);
+
+    let dynamic_state = DynamicState {
+        viewports: Some(vec![Viewport {
+            origin: [0.0, 0.0],
+            dimensions: [dimensions[0] as f32, dimensions[1] as f32],
+            depth_range: 0.0..1.0,
+        }]),
+        ..DynamicState::none()
+    };
+
+    let builder = AutoCommandBufferBuilder::primary_one_time_submit(device.clone(), queue.family())
+        .unwrap()
+        .begin_render_pass(
+            framebuffer,
+            false,
+            depth_clear_values(samples, [0.1, 0.1, 0.1, 1.0]),
+        )
+        .unwrap();
+
+    let builder = renderer
+        .draw(
+            builder,
+            &dynamic_state,
+            dimensions[0] as f32 / dimensions[1] as f32,
+            angle,
+        )
+        .end_render_pass()
+        .unwrap();
+
+    let command_buffer = readback.copy_from(builder, image.clone()).build().unwrap();
+
+    let finished = command_buffer.execute(queue.clone()).unwrap();
+    finished
+        .then_signal_fence_and_flush()
+        .unwrap()
+        .wait(None)
+        .unwrap();
+
+    readback.pixels()
+}
+
+/// Shows `mesh` slowly turning in a window.
+pub fn mesh_window<M: AsRef<Path>>(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    instance: Arc<Instance>,
+    mesh: M,
+    samples: u32,
+    present_mode: PresentMode,
+) {
+    let mesh = Mesh::load_obj(mesh).expect("failed to load mesh");
+
+    turntable_window(
+        device.clone(),
+        queue.clone(),
+        instance,
+        samples,
+        present_mode,
+        move |render_pass| MeshRenderer::new(device, queue, render_pass, &mesh),
+    );
+}
+
+/// Shows what `renderer` creates for the render pass it is given slowly turning in a window.
+pub fn turntable_window<R, F>(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    instance: Arc<Instance>,
+    samples: u32,
+    present_mode: PresentMode,
+    renderer: F,
+) where
+    R: Turntable + 'static,
+    F: FnOnce(Arc<dyn RenderPassAbstract + Send + Sync>) -> R,
+{
+    run_app(
+        device,
+        queue,
+        instance,
+        AppOptions {
+            title: "Turntable".to_string(),
+            samples,
+            present_mode,
+            frames_in_flight: 2,
+            depth: true,
+        },
+        |context| TurntableApp {
+            renderer: renderer(context.render_pass.clone()),
+            aspect: 1.0,
+            angle: 0.0,
+        },
+    );
+}
+
+/// Turns a `Turntable` at a constant speed.
+struct TurntableApp<R> {
+    renderer: R,
+    aspect: f32,
+    angle: f32,
+}
+
+impl<R: Turntable> App for TurntableApp<R> {
+    fn update(&mut self, delta_time: f32) {
+        self.angle += delta_time * 0.5;
+    }
+
+    fn resize(&mut self, dimensions: [u32; 2]) {
+        self.aspect = dimensions[0] as f32 / dimensions[1] as f32;
+    }
+
+    fn clear_color(&self) -> [f32; 4] {
+        [0.1, 0.1, 0.1, 1.0]
+    }
+
+    fn record(
+        &mut self,
+        builder: AutoCommandBufferBuilder,
+        dynamic_state: &DynamicState,
+        _frame: &FrameInfo,
+    ) -> AutoCommandBufferBuilder {
+        self.renderer
+            .draw(builder, dynamic_state, self.aspect, self.angle)
+    }
+}
+
+mod vs_mesh {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        path: "shaders/mesh.vert.glsl"
+    }
+}
+
+mod fs_mesh {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "shaders/mesh.frag.glsl"
+    }
+}
@@ -1,15 +1,76 @@
+mod app;
+mod attractors;
+mod barnes_hut;
+mod camera;
 mod compute_mandelbrot;
 mod compute_shader;
 mod copy_buffers;
+mod export;
+mod format_matrix;
 mod graphics_pipeline;
 mod graphics_window;
+mod gui;
+mod hud;
+mod image_blit;
 mod image_clear;
+mod image_filter;
+mod instancing;
+mod mandel_explorer;
+mod mesh;
+mod mesh_renderer;
+mod msaa;
+mod nbody;
+mod readback;
+mod scene;
+mod scene_renderer;
+mod screenshot;
+mod swapchain;
+mod textured_quad;
 mod vulkano_particles;
 
-pub use compute_mandelbrot::compute_mandel_and_save;
+pub use app::{run_app, App, AppContext, AppOptions, FrameInfo, MAX_FRAMES_IN_FLIGHT};
+pub use attractors::{Attractor, Attractors};
+pub use barnes_hut::{barnes_hut_check, BarnesHut};
+pub use camera::Camera2d;
+pub use compute_mandelbrot::{
+    compute_mandel, compute_mandel_and_save, DownsampleFilter, SamplePattern, Supersampling,
+};
 pub use compute_shader::compute_shader_multiply;
 pub use copy_buffers::copy_buffers;
-pub use graphics_pipeline::graphics_pipeline;
+pub use export::ImageOutput;
+pub use format_matrix::{format_matrix, format_matrix_check, FormatResult};
+pub use graphics_pipeline::{graphics_pipeline, render_triangle};
 pub use graphics_window::graphics_window;
-pub use image_clear::image_clear_and_save;
-pub use vulkano_particles::graphics_window as vulkano_particles;
+pub use gui::{present_mode_buttons, Gui};
+pub use hud::Hud;
+pub use image_blit::{
+    generate_mip_chain, image_blit_and_save, mip_chain_check, read_image, read_mip, resize,
+    BlitSupport, MipChain, SizedImage,
+};
+pub use image_clear::{image_clear, image_clear_and_save};
+pub use image_filter::{
+    edge_filters, filter_on_cpu, filter_on_gpu, image_filter_and_save, image_filter_check, Filter,
+};
+pub use instancing::{
+    instanced_quads_and_save, instanced_quads_window, render_instanced_quads, ColoredVertex,
+    InstancedQuads, QuadInstance,
+};
+pub use mandel_explorer::mandel_explorer;
+pub use mesh::{Mesh, MeshVertex};
+pub use mesh_renderer::{
+    mesh_offscreen_and_save, mesh_window, render_mesh, render_turntable, turntable_window,
+    MeshRenderer, Turntable,
+};
+pub use nbody::{EnergyReadout, NBody, NBodyParams, Solver};
+pub use readback::{Pixels, ReadbackBuffer};
+pub use scene::Scene;
+pub use scene_renderer::{render_scene, scene_offscreen_and_save, scene_window, SceneRenderer};
+pub use screenshot::{screenshot_path, Screenshot};
+pub use swapchain::parse_present_mode;
+pub use textured_quad::{
+    render_textured_quad, textured_quad_and_save, textured_quad_window, SamplerOptions,
+    TexturedQuad,
+};
+pub use vulkano_particles::{
+    graphics_window as vulkano_particles, max_particle_count, Distribution, ParticleOptions,
+};
@@ -0,0 +1,251 @@
+use std::sync::Arc;
+use vulkano::device::Device;
+use vulkano::format::{ClearValue, Format};
+use vulkano::framebuffer::{Framebuffer, FramebufferAbstract, RenderPassAbstract};
+use vulkano::image::{AttachmentImage, ImageViewAccess};
+use vulkano::instance::PhysicalDevice;
+
+/// The largest sample count up to `requested` that color framebuffers of `physical` support,
+/// and depth framebuffers as well with `depth`.
+pub fn supported_samples(physical: PhysicalDevice, requested: u32, depth: bool) -> u32 {
+    let limits = physical.limits();
+    let mut supported = limits.framebuffer_color_sample_counts();
+    if depth {
+        supported &= limits.framebuffer_depth_sample_counts();
+    }
+
+    let mut samples = requested.max(1).next_power_of_two().min(64);
+    if samples > requested.max(1) {
+        samples /= 2;
+    }
+    while samples > 1 && supported & samples == 0 {
+        samples /= 2;
+    }
+
+    if samples != requested {
+        println!(
+            "{} samples are not supported, using {} instead",
+            requested, samples
+        );
+    }
+
+    samples
+}
+
+/// A single pass render pass with one color attachment of `format`. With more than one sample
+/// it renders into a transient multisampled attachment that is resolved into the second one.
+pub fn color_render_pass(
+    device: Arc<Device>,
+    format: Format,
+    samples: u32,
+) -> Arc<dyn RenderPassAbstract + Send + Sync> {
+    if samples == 1 {
+        Arc::new(
+            vulkano::single_pass_renderpass!(device,
+                attachments: {
+                    color: {
+                        load: Clear,
+                        store: Store,
+                        format: format,
+                        samples: 1,
+                    }
+                },
+                pass: {
+                    color: [color],
+                    depth_stencil: {}
+                }
+            )
+            .unwrap(),
+        )
+    } else {
+        Arc::new(
+            vulkano::single_pass_renderpass!(device,
+                attachments: {
+                    multisampled: {
+                        load: Clear,
+                        store: DontCare,
+                        format: format,
+                        samples: samples,
+                    },
+                    color: {
+                        load: DontCare,
+                        store: Store,
+                        format: format,
+                        samples: 1,
+                    }
+                },
+                pass: {
+                    color: [multisampled],
+                    depth_stencil: {},
+                    resolve: [color],
+                }
+            )
+            .unwrap(),
+        )
+    }
+}
+
+/// A framebuffer for `color_render_pass` that ends up in `target`.
+pub fn color_framebuffer<I>(
+    device: Arc<Device>,
+    render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
+    target: I,
+    dimensions: [u32; 2],
+    format: Format,
+    samples: u32,
+) -> Arc<dyn FramebufferAbstract + Send + Sync>
+where
+    I: ImageViewAccess + Send + Sync + 'static,
+{
+    if samples == 1 {
+        Arc::new(
+            Framebuffer::start(render_pass)
+                .add(target)
+                .unwrap()
+                .build()
+                .unwrap(),
+        )
+    } else {
+        let multisampled =
+            AttachmentImage::transient_multisampled(device, dimensions, samples, format).unwrap();
+
+        Arc::new(
+            Framebuffer::start(render_pass)
+                .add(multisampled)
+                .unwrap()
+                .add(target)
+                .unwrap()
+                .build()
+                .unwrap(),
+        )
+    }
+}
+
+/// Clear values for `color_render_pass`, the resolve attachment is never cleared.
+pub fn clear_values(samples: u32, color: [f32; 4]) -> Vec<ClearValue> {
+    if samples == 1 {
+        vec![color.into()]
+    } else {
+        vec![color.into(), ClearValue::None]
+    }
+}
+
+/// Depth format of `depth_render_pass`, every implementation supports it as a depth attachment.
+pub const DEPTH_FORMAT: Format = Format::D16Unorm;
+
+/// Like `color_render_pass`, with an additional depth attachment that is cleared to the far plane.
+pub fn depth_render_pass(
+    device: Arc<Device>,
+    format: Format,
+    samples: u32,
+) -> Arc<dyn RenderPassAbstract + Send + Sync> {
+    if samples == 1 {
+        Arc::new(
+            vulkano::single_pass_renderpass!(device,
+                attachments: {
+                    color: {
+                        load: Clear,
+                        store: Store,
+                        format: format,
+                        samples: 1,
+                    },
+                    depth: {
+                        load: Clear,
+                        store: DontCare,
+                        format: DEPTH_FORMAT,
+                        samples: 1,
+                    }
+                },
+                pass: {
+                    color: [color],
+                    depth_stencil: {depth}
+                }
+            )
+            .unwrap(),
+        )
+    } else {
+        Arc::new(
+            vulkano::single_pass_renderpass!(device,
+                attachments: {
+                    multisampled: {
+                        load: Clear,
+                        store: DontCare,
+                        format: format,
+                        samples: samples,
+                    },
+                    color: {
+                        load: DontCare,
+                        store: Store,
+                        format: format,
+                        samples: 1,
+                    },
+                    depth: {
+                        load: Clear,
+                        store: DontCare,
+                        format: DEPTH_FORMAT,
+                        samples: samples,
+                    }
+                },
+                pass: {
+                    color: [multisampled],
+                    depth_stencil: {depth},
+                    resolve: [color],
+                }
+            )
+            .unwrap(),
+        )
+    }
+}
+
+/// A framebuffer for `depth_render_pass` that ends up in `target`.
+pub fn depth_framebuffer<I>(
+    device: Arc<Device>,
+    render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
+    target: I,
+    dimensions: [u32; 2],
+    format: Format,
+    samples: u32,
+) -> Arc<dyn FramebufferAbstract + Send + Sync>
+where
+    I: ImageViewAccess + Send + Sync + 'static,
+{
+    if samples == 1 {
+        let depth = AttachmentImage::transient(device, dimensions, DEPTH_FORMAT).unwrap();
+
+        Arc::new(
+            Framebuffer::start(render_pass)
+                .add(target)
+                .unwrap()
+                .add(depth)
+                .unwrap()
+                .build()
+                .unwrap(),
+        )
+    } else {
+        let multisampled =
+            AttachmentImage::transient_multisampled(device.clone(), dimensions, samples, format)
+                .unwrap();
+        let depth =
+            AttachmentImage::transient_multisampled(device, dimensions, samples, DEPTH_FORMAT)
+                .unwrap();
+
+        Arc::new(
+            Framebuffer::start(render_pass)
+                .add(multisampled)
+                .unwrap()
+                .add(target)
+                .unwrap()
+                .add(depth)
+                .unwrap()
+                .build()
+                .unwrap(),
+        )
+    }
+}
+
+/// Clear values for `depth_render_pass`.
+pub fn depth_clear_values(samples: u32, color: [f32; 4]) -> Vec<ClearValue> {
+    let mut values = clear_values(samples, color);
+    values.push(ClearValue::Depth(1.0));
+    values
+}
@@ -0,0 +1,288 @@
+use crate::examples::barnes_hut::BarnesHut;
+use crate::examples::vulkano_particles::{particle_set, Vertex};
+use std::sync::Arc;
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer, DeviceLocalBuffer};
+use vulkano::command_buffer::AutoCommandBufferBuilder;
+use vulkano::descriptor::descriptor_set::{DescriptorSet, PersistentDescriptorSet};
+use vulkano::descriptor::PipelineLayoutAbstract;
+use vulkano::device::Queue;
+use vulkano::pipeline::{ComputePipeline, ComputePipelineAbstract};
+
+/// Bodies per tile of the force and energy shaders.
+const TILE_SIZE: u32 = 256;
+
+/// Invocations per workgroup of the drift shader.
+const DRIFT_WORKGROUP_SIZE: u32 = 1024;
+
+/// Bodies the direct solver sums up at most, about 4 * 10^9 interactions per step. Beyond that a
+/// single dispatch runs long enough for the driver to declare the device lost, so larger counts
+/// fall back to Barnes-Hut.
+pub const MAX_DIRECT_BODIES: u32 = 65_536;
+
+/// Workgroups the energy shader can write sums for. The energy is an O(n^2) sum like the direct
+/// solver, so it is measured up to the same count.
+const MAX_ENERGY_GROUPS: u32 = MAX_DIRECT_BODIES / TILE_SIZE;
+
+/// How the gravity of all bodies is summed up.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Solver {
+    /// Every body with every other one, exact.
+    Direct,
+    /// Far away groups of bodies as one, see `BarnesHut`.
+    BarnesHut,
+}
+
+/// Constants of the simulated universe.
+#[derive(Copy, Clone, Debug)]
+pub struct NBodyParams {
+    pub gravity: f32,
+    /// Plummer softening length, keeps close encounters finite.
+    pub softening: f32,
+    /// Every body weighs the same.
+    pub body_mass: f32,
+    /// Opening angle of the Barnes-Hut solver, tree nodes smaller than this times their distance
+    /// count as one body. 0 opens every node.
+    pub theta: f32,
+}
+
+/// Totals over all bodies, in f64 so the sum of the per-workgroup sums keeps its precision.
+#[derive(Copy, Clone, Debug)]
+pub struct EnergyReadout {
+    pub kinetic: f64,
+    pub potential: f64,
+    pub momentum: [f64; 2],
+}
+
+impl EnergyReadout {
+    pub fn total(&self) -> f64 {
+        self.kinetic + self.potential
+    }
+}
+
+/// Energy sums one frame in flight writes.
+struct EnergyFrame {
+    sums: Arc<CpuAccessibleBuffer<[[f32; 4]]>>,
+    sum_set: Arc<dyn DescriptorSet + Send + Sync>,
+    /// Workgroups the last submission with this frame wrote sums for.
+    pending_groups: u32,
+}
+
+/// Every particle attracts every other one, O(n^2) per step with the direct solver.
+///
+/// A step kicks all velocities with the gravity of all positions, then drifts all positions with
+/// the new velocities (semi-implicit Euler). The two passes are separate dispatches so no
+/// workgroup reads a position another one already moved.
+pub struct NBody {
+    kick_pipeline: Arc<dyn ComputePipelineAbstract + Send + Sync>,
+    drift_pipeline: Arc<dyn ComputePipelineAbstract + Send + Sync>,
+    energy_pipeline: Arc<dyn ComputePipelineAbstract + Send + Sync>,
+    kick_set: Arc<dyn DescriptorSet + Send + Sync>,
+    drift_set: Arc<dyn DescriptorSet + Send + Sync>,
+    energy_set: Arc<dyn DescriptorSet + Send + Sync>,
+    barnes_hut: BarnesHut,
+    frames: Vec<EnergyFrame>,
+    particle_count: u32,
+}
+
+impl NBody {
+    pub fn new(
+        queue: Arc<Queue>,
+        particles: Arc<DeviceLocalBuffer<[Vertex]>>,
+        particle_count: u32,
+        frames_in_flight: usize,
+    ) -> NBody {
+        let device = queue.device().clone();
+        let kick_shader =
+            cs_nbody::Shader::load(device.clone()).expect("failed to load kick_shader");
+        let kick_pipeline: Arc<dyn ComputePipelineAbstract + Send + Sync> = Arc::new(
+            ComputePipeline::new(device.clone(), &kick_shader.main_entry_point(), &())
+                .expect("failed to create kick_pipeline"),
+        );
+
+        let drift_shader =
+            cs_drift::Shader::load(device.clone()).expect("failed to load drift_shader");
+        let drift_pipeline: Arc<dyn ComputePipelineAbstract + Send + Sync> = Arc::new(
+            ComputePipeline::new(device.clone(), &drift_shader.main_entry_point(), &())
+                .expect("failed to create drift_pipeline"),
+        );
+
+        let energy_shader =
+            cs_energy::Shader::load(device.clone()).expect("failed to load energy_shader");
+        let energy_pipeline: Arc<dyn ComputePipelineAbstract + Send + Sync> = Arc::new(
+            ComputePipeline::new(device.clone(), &energy_shader.main_entry_point(), &())
+                .expect("failed to create energy_pipeline"),
+        );
+
+        let sum_layout = energy_pipeline.layout().descriptor_set_layout(1).unwrap();
+        let frames = (0..frames_in_flight)
+            .map(|_| {
+                let sums = CpuAccessibleBuffer::from_iter(
+                    device.clone(),
+                    BufferUsage {
+                        storage_buffer: true,
+                        ..BufferUsage::none()
+                    },
+                    false,
+                    (0..MAX_ENERGY_GROUPS).map(|_| [0.0; 4]),
+                )
+                .expect("failed to create energy sums");
+
+                EnergyFrame {
+                    sum_set: Arc::new(
+                        PersistentDescriptorSet::start(sum_layout.clone())
+                            .add_buffer(sums.clone())
+                            .unwrap()
+                            .build()
+                            .unwrap(),
+                    ),
+                    sums,
+                    pending_groups: 0,
+                }
+            })
+            .collect();
+
+        NBody {
+            kick_set: particle_set(&kick_pipeline, particles.clone()),
+            drift_set: particle_set(&drift_pipeline, particles.clone()),
+            energy_set: particle_set(&energy_pipeline, particles.clone()),
+            barnes_hut: BarnesHut::new(queue, particles, particle_count),
+            kick_pipeline,
+            drift_pipeline,
+            energy_pipeline,
+            frames,
+            particle_count,
+        }
+    }
+
+    /// Simulates `particles` from now on, after a respawn.
+    pub fn set_particles(&mut self, particles: Arc<DeviceLocalBuffer<[Vertex]>>, count: u32) {
+        self.kick_set = particle_set(&self.kick_pipeline, particles.clone());
+        self.drift_set = particle_set(&self.drift_pipeline, particles.clone());
+        self.energy_set = particle_set(&self.energy_pipeline, particles.clone());
+        self.barnes_hut.set_particles(particles, count);
+        self.particle_count = count;
+        for frame in &mut self.frames {
+            frame.pending_groups = 0;
+        }
+    }
+
+    /// Records kicking every velocity with the gravity of `delta_time` seconds, as `solver` sums
+    /// it up. More than `MAX_DIRECT_BODIES` are always summed up with Barnes-Hut.
+    pub fn kick(
+        &self,
+        builder: AutoCommandBufferBuilder,
+        params: NBodyParams,
+        delta_time: f32,
+        solver: Solver,
+    ) -> AutoCommandBufferBuilder {
+        if solver == Solver::BarnesHut || self.particle_count > MAX_DIRECT_BODIES {
+            return self.barnes_hut.kick(builder, params, delta_time);
+        }
+
+        builder
+            .dispatch(
+                [(self.particle_count + TILE_SIZE - 1) / TILE_SIZE, 1, 1],
+                self.kick_pipeline.clone(),
+                self.kick_set.clone(),
+                cs_nbody::ty::Params {
+                    delta_time,
+                    gravity: params.gravity,
+                    softening: params.softening,
+                    body_mass: params.body_mass,
+                },
+            )
+            .unwrap()
+    }
+
+    /// Records one step of `delta_time` seconds, followed by the energy sums if `measure` is set
+    /// and there are few enough bodies.
+    pub fn step(
+        &mut self,
+        builder: AutoCommandBufferBuilder,
+        frame_index: usize,
+        params: NBodyParams,
+        delta_time: f32,
+        solver: Solver,
+        measure: bool,
+    ) -> AutoCommandBufferBuilder {
+        let tile_groups = (self.particle_count + TILE_SIZE - 1) / TILE_SIZE;
+
+        let mut builder = self
+            .kick(builder, params, delta_time, solver)
+            .dispatch(
+                [
+                    (self.particle_count + DRIFT_WORKGROUP_SIZE - 1) / DRIFT_WORKGROUP_SIZE,
+                    1,
+                    1,
+                ],
+                self.drift_pipeline.clone(),
+                self.drift_set.clone(),
+                cs_drift::ty::Params { delta_time },
+            )
+            .unwrap();
+
+        let frame = &mut self.frames[frame_index];
+        frame.pending_groups = 0;
+        if measure && tile_groups <= MAX_ENERGY_GROUPS {
+            builder = builder
+                .dispatch(
+                    [tile_groups, 1, 1],
+                    self.energy_pipeline.clone(),
+                    (self.energy_set.clone(), frame.sum_set.clone()),
+                    cs_energy::ty::Params {
+                        gravity: params.gravity,
+                        softening: params.softening,
+                        body_mass: params.body_mass,
+                    },
+                )
+                .unwrap();
+            frame.pending_groups = tile_groups;
+        }
+
+        builder
+    }
+
+    /// The sums the last submission with `frame_index` measured. Only call this once the runner
+    /// waited for that submission, i.e. before recording the frame's next step.
+    pub fn readout(&self, frame_index: usize) -> Option<EnergyReadout> {
+        let frame = &self.frames[frame_index];
+        if frame.pending_groups == 0 {
+            return None;
+        }
+
+        let sums = frame.sums.read().unwrap();
+        let mut readout = EnergyReadout {
+            kinetic: 0.0,
+            potential: 0.0,
+            momentum: [0.0, 0.0],
+        };
+        for sum in &sums[..frame.pending_groups as usize] {
+            readout.kinetic += sum[0] as f64;
+            readout.potential += sum[1] as f64;
+            readout.momentum[0] += sum[2] as f64;
+            readout.momentum[1] += sum[3] as f64;
+        }
+        Some(readout)
+    }
+}
+
+mod cs_nbody {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/particle_nbody.comp.glsl"
+    }
+}
+
+mod cs_drift {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/particle_drift.comp.glsl"
+    }
+}
+
+mod cs_energy {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/particle_energy.comp.glsl"
+    }
+}
@@ -0,0 +1,476 @@
+use crate::examples::export::{f16_to_f32, save_exr, save_hdr, save_npy};
+use image::{DynamicImage, ImageBuffer};
+use std::error::Error;
+use std::path::Path;
+use std::sync::Arc;
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer};
+use vulkano::command_buffer::AutoCommandBufferBuilder;
+use vulkano::device::Device;
+use vulkano::format::Format;
+use vulkano::image::ImageAccess;
+
+/// A host visible buffer an offscreen image can be copied into.
+///
+/// Record the copy with `copy_from` into the same command buffer that renders the image, wait
+/// for it to finish and then fetch the result with `pixels`.
+pub struct ReadbackBuffer {
+    buffer: Arc<CpuAccessibleBuffer<[u8]>>,
+    width: u32,
+    height: u32,
+    format: Format,
+}
+
+impl ReadbackBuffer {
+    pub fn new(device: Arc<Device>, dimensions: [u32; 2], format: Format) -> ReadbackBuffer {
+        let texel_size = format
+            .size()
+            .unwrap_or_else(|| panic!("{:?} has no fixed texel size", format));
+        let len = dimensions[0] as usize * dimensions[1] as usize * texel_size;
+
+        let buffer = CpuAccessibleBuffer::from_iter(
+            device,
+            BufferUsage {
+                transfer_destination: true,
+                ..BufferUsage::none()
+            },
+            false,
+            (0..len).map(|_| 0u8),
+        )
+        .expect("failed to create readback buffer");
+
+        ReadbackBuffer {
+            buffer,
+            width: dimensions[0],
+            height: dimensions[1],
+            format,
+        }
+    }
+
+    /// Appends a copy of `image` into this buffer to `builder`.
+    pub fn copy_from<I>(
+        &self,
+        builder: AutoCommandBufferBuilder,
+        image: I,
+    ) -> AutoCommandBufferBuilder
+    where
+        I: ImageAccess + Send + Sync + 'static,
+    {
+        builder
+            .copy_image_to_buffer(image, self.buffer.clone())
+            .expect("failed to record image readback")
+    }
+
+    /// Like `copy_from`, but copies mip `level`, which has to have the dimensions of this buffer.
+    pub fn copy_level_from<I>(
+        &self,
+        builder: AutoCommandBufferBuilder,
+        image: I,
+        level: u32,
+    ) -> AutoCommandBufferBuilder
+    where
+        I: ImageAccess + Send + Sync + 'static,
+    {
+        builder
+            .copy_image_to_buffer_dimensions(
+                image,
+                self.buffer.clone(),
+                [0, 0, 0],
+                [self.width, self.height, 1],
+                0,
+                1,
+                level,
+            )
+            .expect("failed to record mip readback")
+    }
+
+    /// The texels copied by the last finished `copy_from`.
+    pub fn pixels(&self) -> Pixels {
+        Pixels {
+            width: self.width,
+            height: self.height,
+            format: self.format,
+            texels: self.buffer.read().unwrap().to_vec(),
+        }
+    }
+}
+
+/// The numeric interpretation of the channels of a format.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ChannelKind {
+    Unorm,
+    Snorm,
+    Uint,
+    Sint,
+    Sfloat,
+    Srgb,
+}
+
+/// Memory layout of the formats `Pixels` knows how to decode.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FormatLayout {
+    pub kind: ChannelKind,
+    pub channels: usize,
+    pub channel_size: usize,
+    /// Channels are stored as BGR(A) instead of RGB(A).
+    pub bgra: bool,
+}
+
+impl FormatLayout {
+    pub fn of(format: Format) -> Option<FormatLayout> {
+        use ChannelKind::*;
+
+        let (kind, channels, channel_size, bgra) = match format {
+            Format::R8Unorm => (Unorm, 1, 1, false),
+            Format::R8G8Unorm => (Unorm, 2, 1, false),
+            Format::R8G8B8A8Unorm => (Unorm, 4, 1, false),
+            Format::B8G8R8A8Unorm => (Unorm, 4, 1, true),
+            Format::R8Snorm => (Snorm, 1, 1, false),
+            Format::R8G8B8A8Snorm => (Snorm, 4, 1, false),
+            Format::R8Uint => (Uint, 1, 1, false),
+            Format::R8G8B8A8Uint => (Uint, 4, 1, false),
+            Format::R8Sint => (Sint, 1, 1, false),
+            Format::R8G8B8A8Sint => (Sint, 4, 1, false),
+            Format::R8Srgb => (Srgb, 1, 1, false),
+            Format::R8G8B8A8Srgb => (Srgb, 4, 1, false),
+            Format::B8G8R8A8Srgb => (Srgb, 4, 1, true),
+            Format::R16Unorm => (Unorm, 1, 2, false),
+            Format::R16G16B16A16Unorm => (Unorm, 4, 2, false),
+            Format::R16Snorm => (Snorm, 1, 2, false),
+            Format::R16G16B16A16Snorm => (Snorm, 4, 2, false),
+            Format::R16Uint => (Uint, 1, 2, false),
+            Format::R16G16B16A16Uint => (Uint, 4, 2, false),
+            Format::R16Sint => (Sint, 1, 2, false),
+            Format::R16G16B16A16Sint => (Sint, 4, 2, false),
+            Format::R16Sfloat => (Sfloat, 1, 2, false),
+            Format::R16G16Sfloat => (Sfloat, 2, 2, false),
+            Format::R16G16B16A16Sfloat => (Sfloat, 4, 2, false),
+            Format::R32Uint => (Uint, 1, 4, false),
+            Format::R32G32B32A32Uint => (Uint, 4, 4, false),
+            Format::R32Sint => (Sint, 1, 4, false),
+            Format::R32G32B32A32Sint => (Sint, 4, 4, false),
+            Format::R32Sfloat => (Sfloat, 1, 4, false),
+            Format::R32G32Sfloat => (Sfloat, 2, 4, false),
+            Format::R32G32B32A32Sfloat => (Sfloat, 4, 4, false),
+            _ => return None,
+        };
+
+        Some(FormatLayout {
+            kind,
+            channels,
+            channel_size,
+            bgra,
+        })
+    }
+}
+
+/// Texels read back from the GPU, still in the layout of `format`.
+#[derive(Clone, Debug)]
+pub struct Pixels {
+    pub width: u32,
+    pub height: u32,
+    pub format: Format,
+    pub texels: Vec<u8>,
+}
+
+impl Pixels {
+    /// Decodes every texel to the RGBA value a shader would read, i.e. normalized formats are
+    /// scaled, sRGB is linearized and missing channels default to `(0, 0, 0, 1)`.
+    pub fn to_rgba_f32(&self) -> Result<Vec<[f32; 4]>, Box<dyn Error>> {
+        if is_packed(self.format) {
+            let texel_size = self.format.size().unwrap();
+            return Ok(self
+                .texels
+                .chunks_exact(texel_size)
+                .map(|texel| {
+                    let bits = texel
+                        .iter()
+                        .rev()
+                        .fold(0u32, |acc, &byte| (acc << 8) | byte as u32);
+                    decode_packed(self.format, bits)
+                })
+                .collect());
+        }
+
+        let layout = FormatLayout::of(self.format)
+            .ok_or_else(|| format!("reading back {:?} is not supported", self.format))?;
+        let texel_size = layout.channels * layout.channel_size;
+
+        Ok(self
+            .texels
+            .chunks_exact(texel_size)
+            .map(|texel| {
+                let mut rgba = [0.0, 0.0, 0.0, 1.0];
+                for (c, value) in rgba.iter_mut().enumerate().take(layout.channels) {
+                    let bytes = &texel[c * layout.channel_size..][..layout.channel_size];
+                    *value = decode_channel(layout.kind, bytes, c == 3);
+                }
+                if layout.bgra {
+                    rgba.swap(0, 2);
+                }
+                rgba
+            })
+            .collect())
+    }
+
+    /// Converts to the `image` pixel type matching the format. 8 bit formats keep their bytes
+    /// (BGRA is swizzled to RGBA), everything else is clamped to `[0, 1]` and stored with 16 bits
+    /// per channel.
+    pub fn to_image(&self) -> Result<DynamicImage, Box<dyn Error>> {
+        let (width, height) = (self.width, self.height);
+        let too_small = "texel data too small for image";
+
+        let image = match self.format {
+            Format::R8Unorm | Format::R8Srgb => DynamicImage::ImageLuma8(
+                ImageBuffer::from_raw(width, height, self.texels.clone()).ok_or(too_small)?,
+            ),
+            Format::R8G8B8A8Unorm | Format::R8G8B8A8Srgb => DynamicImage::ImageRgba8(
+                ImageBuffer::from_raw(width, height, self.texels.clone()).ok_or(too_small)?,
+            ),
+            Format::B8G8R8A8Unorm | Format::B8G8R8A8Srgb => {
+                let rgba = self
+                    .texels
+                    .chunks_exact(4)
+                    .flat_map(|bgra| vec![bgra[2], bgra[1], bgra[0], bgra[3]])
+                    .collect();
+                DynamicImage::ImageRgba8(
+                    ImageBuffer::from_raw(width, height, rgba).ok_or(too_small)?,
+                )
+            }
+            _ => {
+                let rgba = self.to_rgba_f32()?;
+                let to_u16 = |v: f32| (v.max(0.0).min(1.0) * 65535.0).round() as u16;
+
+                if FormatLayout::of(self.format).map_or(false, |l| l.channels == 1) {
+                    let luma = rgba.iter().map(|p| to_u16(p[0])).collect();
+                    DynamicImage::ImageLuma16(
+                        ImageBuffer::from_raw(width, height, luma).ok_or(too_small)?,
+                    )
+                } else {
+                    let rgba = rgba
+                        .iter()
+                        .flat_map(|p| p.iter().map(|&v| to_u16(v)))
+                        .collect();
+                    DynamicImage::ImageRgba16(
+                        ImageBuffer::from_raw(width, height, rgba).ok_or(too_small)?,
+                    )
+                }
+            }
+        };
+
+        Ok(image)
+    }
+
+    /// Saves to `path`, picking the encoder from its extension. `.hdr` and `.exr` keep the full
+    /// float range, `.npy` dumps the first channel, anything else goes through `to_image`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+
+        match extension.as_ref().map(|e| e.as_str()) {
+            Some("hdr") => save_hdr(path, self.width, self.height, &self.flat_rgba_f32()?),
+            Some("exr") => save_exr(path, self.width, self.height, &self.flat_rgba_f32()?),
+            Some("npy") => {
+                let values = self.to_rgba_f32()?.iter().map(|p| p[0]).collect::<Vec<_>>();
+                save_npy(path, self.width, self.height, &values)
+            }
+            _ => {
+                self.to_image()?.save(path)?;
+                Ok(())
+            }
+        }
+    }
+
+    fn flat_rgba_f32(&self) -> Result<Vec<f32>, Box<dyn Error>> {
+        Ok(self.to_rgba_f32()?.iter().flatten().cloned().collect())
+    }
+}
+
+fn decode_channel(kind: ChannelKind, bytes: &[u8], alpha: bool) -> f32 {
+    let unsigned = bytes
+        .iter()
+        .rev()
+        .fold(0u64, |acc, &byte| (acc << 8) | byte as u64);
+    let bits = bytes.len() as u32 * 8;
+    let signed = ((unsigned << (64 - bits)) as i64) >> (64 - bits);
+    let max_unsigned = ((1u64 << bits) - 1) as f32;
+    let max_signed = ((1u64 << (bits - 1)) - 1) as f32;
+
+    match kind {
+        ChannelKind::Unorm => unsigned as f32 / max_unsigned,
+        ChannelKind::Snorm => (signed as f32 / max_signed).max(-1.0),
+        ChannelKind::Uint => unsigned as f32,
+        ChannelKind::Sint => signed as f32,
+        ChannelKind::Sfloat if bits == 16 => f16_to_f32(unsigned as u16),
+        ChannelKind::Sfloat => f32::from_bits(unsigned as u32),
+        // Alpha is never sRGB encoded.
+        ChannelKind::Srgb if alpha => unsigned as f32 / max_unsigned,
+        ChannelKind::Srgb => srgb_to_linear(unsigned as f32 / max_unsigned),
+    }
+}
+
+/// Whether `format` packs all channels into a single 16 or 32 bit word.
+pub fn is_packed(format: Format) -> bool {
+    match format {
+        Format::R4G4B4A4UnormPack16
+        | Format::R5G6B5UnormPack16
+        | Format::B5G6R5UnormPack16
+        | Format::A1R5G5B5UnormPack16
+        | Format::A8B8G8R8UnormPack32
+        | Format::A8B8G8R8SrgbPack32
+        | Format::A2B10G10R10UnormPack32
+        | Format::A2B10G10R10UintPack32
+        | Format::A2R10G10B10UnormPack32
+        | Format::B10G11R11UfloatPack32
+        | Format::E5B9G9R9UfloatPack32 => true,
+        _ => false,
+    }
+}
+
+/// Decodes a texel of a packed format. The name lists the channels from the most to the least
+/// significant bits.
+fn decode_packed(format: Format, bits: u32) -> [f32; 4] {
+    let field = |shift: u32, width: u32| (bits >> shift) & ((1 << width) - 1);
+    let unorm = |shift: u32, width: u32| field(shift, width) as f32 / ((1 << width) - 1) as f32;
+
+    match format {
+        Format::R4G4B4A4UnormPack16 => [unorm(12, 4), unorm(8, 4), unorm(4, 4), unorm(0, 4)],
+        Format::R5G6B5UnormPack16 => [unorm(11, 5), unorm(5, 6), unorm(0, 5), 1.0],
+        Format::B5G6R5UnormPack16 => [unorm(0, 5), unorm(5, 6), unorm(11, 5), 1.0],
+        Format::A1R5G5B5UnormPack16 => [unorm(10, 5), unorm(5, 5), unorm(0, 5), unorm(15, 1)],
+        Format::A8B8G8R8UnormPack32 => [unorm(0, 8), unorm(8, 8), unorm(16, 8), unorm(24, 8)],
+        Format::A8B8G8R8SrgbPack32 => [
+            srgb_to_linear(unorm(0, 8)),
+            srgb_to_linear(unorm(8, 8)),
+            srgb_to_linear(unorm(16, 8)),
+            unorm(24, 8),
+        ],
+        Format::A2B10G10R10UnormPack32 => {
+            [unorm(0, 10), unorm(10, 10), unorm(20, 10), unorm(30, 2)]
+        }
+        Format::A2B10G10R10UintPack32 => [
+            field(0, 10) as f32,
+            field(10, 10) as f32,
+            field(20, 10) as f32,
+            field(30, 2) as f32,
+        ],
+        Format::A2R10G10B10UnormPack32 => {
+            [unorm(20, 10), unorm(10, 10), unorm(0, 10), unorm(30, 2)]
+        }
+        Format::B10G11R11UfloatPack32 => [
+            small_ufloat(field(0, 11), 6),
+            small_ufloat(field(11, 11), 6),
+            small_ufloat(field(22, 10), 5),
+            1.0,
+        ],
+        Format::E5B9G9R9UfloatPack32 => {
+            let scale = 2f32.powi(field(27, 5) as i32 - 15 - 9);
+            [
+                field(0, 9) as f32 * scale,
+                field(9, 9) as f32 * scale,
+                field(18, 9) as f32 * scale,
+                1.0,
+            ]
+        }
+        _ => panic!("{:?} is not a packed format", format),
+    }
+}
+
+/// Decodes the unsigned 10 and 11 bit floats of `B10G11R11UfloatPack32`, which have a 5 bit
+/// exponent and `mantissa_bits` of mantissa.
+fn small_ufloat(bits: u32, mantissa_bits: u32) -> f32 {
+    let exponent = (bits >> mantissa_bits) as i32;
+    let mantissa = (bits & ((1 << mantissa_bits) - 1)) as f32 / (1 << mantissa_bits) as f32;
+
+    match exponent {
+        0 => mantissa * 2f32.powi(-14),
+        31 if mantissa == 0.0 => f32::INFINITY,
+        31 => f32::NAN,
+        _ => (1.0 + mantissa) * 2f32.powi(exponent - 15),
+    }
+}
+
+pub fn srgb_to_linear(v: f32) -> f32 {
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode_packed;
+    use vulkano::format::Format;
+
+    fn b10g11r11(r: u32, g: u32, b: u32) -> [f32; 4] {
+        decode_packed(Format::B10G11R11UfloatPack32, r | g << 11 | b << 22)
+    }
+
+    #[test]
+    fn b10g11r11_zero_and_one() {
+        assert_eq!(b10g11r11(0, 0, 0), [0.0, 0.0, 0.0, 1.0]);
+        // Exponent 15 and no mantissa in every channel.
+        assert_eq!(b10g11r11(15 << 6, 15 << 6, 15 << 5), [1.0, 1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn b10g11r11_subnormals() {
+        let smallest = b10g11r11(1, 1, 1);
+        assert_eq!(smallest[0], 2f32.powi(-20));
+        assert_eq!(smallest[1], 2f32.powi(-20));
+        assert_eq!(smallest[2], 2f32.powi(-19));
+
+        let largest = b10g11r11(0x3f, 0x3f, 0x1f);
+        assert_eq!(largest[0], 63.0 * 2f32.powi(-20));
+        assert_eq!(largest[2], 31.0 * 2f32.powi(-19));
+    }
+
+    #[test]
+    fn b10g11r11_largest_finite() {
+        let [r, g, b, _] = b10g11r11(30 << 6 | 0x3f, 30 << 6 | 0x3f, 30 << 5 | 0x1f);
+        assert_eq!(r, 65024.0);
+        assert_eq!(g, 65024.0);
+        assert_eq!(b, 64512.0);
+    }
+
+    #[test]
+    fn b10g11r11_infinity_and_nan() {
+        let [r, g, b, _] = b10g11r11(31 << 6, 31 << 6 | 1, 31 << 5);
+        assert_eq!(r, f32::INFINITY);
+        assert!(g.is_nan());
+        assert_eq!(b, f32::INFINITY);
+    }
+
+    #[test]
+    fn e5b9g9r9_shared_exponent() {
+        let decode = |exponent: u32, r: u32, g: u32, b: u32| {
+            decode_packed(
+                Format::E5B9G9R9UfloatPack32,
+                r | g << 9 | b << 18 | exponent << 27,
+            )
+        };
+        assert_eq!(decode(15, 256, 128, 0), [0.5, 0.25, 0.0, 1.0]);
+        assert_eq!(decode(0, 1, 0, 0)[0], 2f32.powi(-24));
+        assert_eq!(decode(31, 511, 511, 511)[0], 65408.0);
+    }
+
+    #[test]
+    fn ten_bit_unorm_channel_order() {
+        let red = 0x3ff << 20;
+        assert_eq!(
+            decode_packed(Format::A2R10G10B10UnormPack32, red),
+            [1.0, 0.0, 0.0, 0.0]
+        );
+        assert_eq!(
+            decode_packed(Format::A2B10G10R10UnormPack32, red),
+            [0.0, 0.0, 1.0, 0.0]
+        );
+        assert_eq!(
+            decode_packed(Format::A2B10G10R10UintPack32, 0xffff_ffff),
+            [1023.0, 1023.0, 1023.0, 3.0]
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use crate::examples::mesh::smooth_normals;
+use cgmath::{Matrix4, SquareMatrix};
+use std::error::Error;
+use std::path::Path;
+
+#[derive(Default, Copy, Clone)]
+pub struct SceneVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub tex_coord: [f32; 2],
+}
+
+vulkano::impl_vertex!(SceneVertex, position, normal, tex_coord);
+
+/// A triangle list of one glTF mesh primitive, placed by the node that instances it.
+pub struct ScenePrimitive {
+    pub vertices: Vec<SceneVertex>,
+    pub indices: Vec<u32>,
+    /// Model matrix accumulated along the node hierarchy.
+    pub transform: Matrix4<f32>,
+    /// Base color factor of the PBR material.
+    pub base_color: [f32; 4],
+    /// Index into `Scene::textures` of the base color texture.
+    pub base_color_texture: Option<usize>,
+}
+
+/// An RGBA8 texture in sRGB.
+pub struct SceneTexture {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+pub enum SceneProjection {
+    /// The aspect ratio is taken from the viewport.
+    Perspective {
+        yfov: f32,
+        znear: f32,
+        zfar: Option<f32>,
+    },
+    Orthographic {
+        xmag: f32,
+        ymag: f32,
+        znear: f32,
+        zfar: f32,
+    },
+}
+
+pub struct SceneCamera {
+    /// Inverse of the camera node's world transform.
+    pub view: Matrix4<f32>,
+    pub projection: SceneProjection,
+}
+
+/// The default scene of a glTF file, flattened into a list of primitives.
+pub struct Scene {
+    pub primitives: Vec<ScenePrimitive>,
+    pub textures: Vec<SceneTexture>,
+    /// The first camera found in the node hierarchy.
+    pub camera: Option<SceneCamera>,
+}
+
+impl Scene {
+    /// Loads the default scene of a glTF or GLB file, or its first scene if there is no default.
+    /// Only triangle list primitives are kept.
+    pub fn load_gltf<P: AsRef<Path>>(path: P) -> Result<Scene, Box<dyn Error>> {
+        let (document, buffers, images) = gltf::import(path.as_ref())?;
+
+        let gltf_scene = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+            .ok_or_else(|| format!("{} contains no scene", path.as_ref().display()))?;
+
+        let mut scene = Scene {
+            primitives: Vec::new(),
+            textures: images.iter().map(SceneTexture::from_gltf).collect(),
+            camera: None,
+        };
+
+        for node in gltf_scene.nodes() {
+            scene.add_node(&node, Matrix4::identity(), &buffers);
+        }
+
+        if scene.primitives.is_empty() {
+            return Err(format!("{} contains no triangles", path.as_ref().display()).into());
+        }
+
+        Ok(scene)
+    }
+
+    fn add_node(
+        &mut self,
+        node: &gltf::Node,
+        parent: Matrix4<f32>,
+        buffers: &[gltf::buffer::Data],
+    ) {
+        let transform = parent * Matrix4::from(node.transform().matrix());
+
+        if let Some(camera) = node.camera().filter(|_| self.camera.is_none()) {
+            self.camera = Some(SceneCamera {
+                view: transform.invert().unwrap_or_else(Matrix4::identity),
+                projection: match camera.projection() {
+                    gltf::camera::Projection::Perspective(perspective) => {
+                        SceneProjection::Perspective {
+                            yfov: perspective.yfov(),
+                            znear: perspective.znear(),
+                            zfar: perspective.zfar(),
+                        }
+                    }
+                    gltf::camera::Projection::Orthographic(orthographic) => {
+                        SceneProjection::Orthographic {
+                            xmag: orthographic.xmag(),
+                            ymag: orthographic.ymag(),
+                            znear: orthographic.znear(),
+                            zfar: orthographic.zfar(),
+                        }
+                    }
+                },
+            });
+        }
+
+        if let Some(mesh) = node.mesh() {
+            for primitive in mesh.primitives() {
+                if primitive.mode() != gltf::mesh::Mode::Triangles {
+                    continue;
+                }
+
+                let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+
+                let positions = match reader.read_positions() {
+                    Some(positions) => positions.collect::<Vec<_>>(),
+                    None => continue,
+                };
+                let indices = match reader.read_indices() {
+                    Some(indices) => indices.into_u32().collect::<Vec<_>>(),
+                    None => (0..positions.len() as u32).collect(),
+                };
+                let normals = match reader.read_normals() {
+                    Some(normals) => normals.collect::<Vec<_>>(),
+                    None => smooth_normals(&positions, &indices),
+                };
+
+                let material = primitive.material().pbr_metallic_roughness();
+                let texture = material.base_color_texture();
+
+                let tex_coords = texture
+                    .as_ref()
+                    .and_then(|info| reader.read_tex_coords(info.tex_coord()))
+                    .map(|tex_coords| tex_coords.into_f32().collect::<Vec<_>>())
+                    .unwrap_or_else(|| vec![[0.0; 2]; positions.len()]);
+
+                self.primitives.push(ScenePrimitive {
+                    vertices: positions
+                        .iter()
+                        .zip(normals.iter())
+                        .zip(tex_coords.iter())
+                        .map(|((&position, &normal), &tex_coord)| SceneVertex {
+                            position,
+                            normal,
+                            tex_coord,
+                        })
+                        .collect(),
+                    indices,
+                    transform,
+                    base_color: material.base_color_factor(),
+                    base_color_texture: texture.map(|info| info.texture().source().index()),
+                });
+            }
+        }
+
+        for child in node.children() {
+            self.add_node(&child, transform, buffers);
+        }
+    }
+
+    /// Center and radius of a sphere enclosing every vertex in world space.
+    pub fn bounding_sphere(&self) -> ([f32; 3], f32) {
+        let world_positions = || {
+            self.primitives.iter().flat_map(|primitive| {
+                primitive.vertices.iter().map(move |vertex| {
+                    let [x, y, z] = vertex.position;
+                    (primitive.transform * cgmath::Vector4::new(x, y, z, 1.0)).truncate()
+                })
+            })
+        };
+
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+
+        for position in world_positions() {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(position[axis]);
+                max[axis] = max[axis].max(position[axis]);
+            }
+        }
+
+        let center = [
+            (min[0] + max[0]) / 2.0,
+            (min[1] + max[1]) / 2.0,
+            (min[2] + max[2]) / 2.0,
+        ];
+        let radius = world_positions()
+            .map(|position| {
+                (0..3)
+                    .map(|axis| (position[axis] - center[axis]).powi(2))
+                    .sum::<f32>()
+                    .sqrt()
+            })
+            .fold(0.0, f32::max);
+
+        (center, radius.max(f32::EPSILON))
+    }
+}
+
+impl SceneTexture {
+    /// Expands the decoded image to RGBA8, keeping the upper byte of 16 bit channels.
+    fn from_gltf(image: &gltf::image::Data) -> SceneTexture {
+        use gltf::image::Format;
+
+        let (channels, bytes_per_channel) = match image.format {
+            Format::R8 => (1, 1),
+            Format::R8G8 => (2, 1),
+            Format::R8G8B8 | Format::B8G8R8 => (3, 1),
+            Format::R8G8B8A8 | Format::B8G8R8A8 => (4, 1),
+            Format::R16 => (1, 2),
+            Format::R16G16 => (2, 2),
+            Format::R16G16B16 => (3, 2),
+            Format::R16G16B16A16 => (4, 2),
+        };
+        let bgr = match image.format {
+            Format::B8G8R8 | Format::B8G8R8A8 => true,
+            _ => false,
+        };
+
+        let pixels = image
+            .pixels
+            .chunks(channels * bytes_per_channel)
+            .flat_map(|texel| {
+                let channel = |i: usize| {
+                    let bytes = &texel[i * bytes_per_channel..(i + 1) * bytes_per_channel];
+                    if bytes_per_channel == 2 {
+                        (u16::from_ne_bytes([bytes[0], bytes[1]]) >> 8) as u8
+                    } else {
+                        bytes[0]
+                    }
+                };
+
+                let rgba = match channels {
+                    1 => [channel(0), channel(0), channel(0), 255],
+                    2 => [channel(0), channel(0), channel(0), channel(1)],
+                    3 => [channel(0), channel(1), channel(2), 255],
+                    _ => [channel(0), channel(1), channel(2), channel(3)],
+                };
+
+                if bgr {
+                    vec![rgba[2], rgba[1], rgba[0], rgba[3]]
+                } else {
+                    rgba.to_vec()
+                }
+            })
+            .collect();
+
+        SceneTexture {
+            width: image.width,
+            height: image.height,
+            pixels,
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use crate::examples::mesh_renderer::{
+    framing_camera, render_turntable, turntable, turntable_window, vulkan_clip, Turntable,
+};
+use crate::examples::readback::Pixels;
+use crate::examples::scene::{Scene, SceneProjection, SceneVertex};
+use cgmath::{Matrix4, Rad, Vector3};
+use std::path::Path;
+use std::sync::Arc;
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer, ImmutableBuffer};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
+use vulkano::descriptor::descriptor_set::{
+    DescriptorSet, FixedSizeDescriptorSetsPool, PersistentDescriptorSet,
+};
+use vulkano::descriptor::PipelineLayoutAbstract;
+use vulkano::device::{Device, Queue};
+use vulkano::format::Format;
+use vulkano::framebuffer::{RenderPassAbstract, Subpass};
+use vulkano::image::{Dimensions, ImmutableImage};
+use vulkano::instance::Instance;
+use vulkano::pipeline::{GraphicsPipeline, GraphicsPipelineAbstract};
+use vulkano::sampler::Sampler;
+use vulkano::swapchain::PresentMode;
+use vulkano::sync::GpuFuture;
+
+/// A primitive uploaded to the GPU.
+struct DrawCall {
+    vertex_buffer: Arc<ImmutableBuffer<[SceneVertex]>>,
+    index_buffer: Arc<ImmutableBuffer<[u32]>>,
+    texture_set: Arc<dyn DescriptorSet + Send + Sync>,
+    transform: Matrix4<f32>,
+    base_color: [f32; 4],
+}
+
+/// Draws every primitive of a scene with its base color, lit like `MeshRenderer`.
+pub struct SceneRenderer {
+    device: Arc<Device>,
+    pipeline: Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
+    draw_calls: Vec<DrawCall>,
+    camera_pool: FixedSizeDescriptorSetsPool,
+    scene_camera: Option<(Matrix4<f32>, SceneProjection)>,
+    center: [f32; 3],
+    radius: f32,
+}
+
+impl SceneRenderer {
+    pub fn new(
+        device: Arc<Device>,
+        queue: Arc<Queue>,
+        render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
+        scene: Scene,
+    ) -> SceneRenderer {
+        let vert_shader =
+            vs_scene::Shader::load(device.clone()).expect("failed to create vert_shader");
+        let frag_shader =
+            fs_scene::Shader::load(device.clone()).expect("failed to create frag_shader");
+
+        let pipeline = Arc::new(
+            GraphicsPipeline::start()
+                .vertex_input_single_buffer::<SceneVertex>()
+                .vertex_shader(vert_shader.main_entry_point(), ())
+                .triangle_list()
+                .viewports_dynamic_scissors_irrelevant(1)
+                .fragment_shader(frag_shader.main_entry_point(), ())
+                .depth_stencil_simple_depth()
+                .render_pass(Subpass::from(render_pass, 0).unwrap())
+                .build(device.clone())
+                .unwrap(),
+        );
+
+        let camera_layout = pipeline.descriptor_set_layout(0).unwrap();
+        let camera_pool = FixedSizeDescriptorSetsPool::new(camera_layout.clone());
+        let texture_layout = pipeline.descriptor_set_layout(1).unwrap();
+
+        let sampler = Sampler::simple_repeat_linear_no_mipmap(device.clone());
+
+        let mut uploads = Vec::new();
+
+        let mut upload_texture = |width, height, pixels: Vec<u8>| {
+            let (image, uploaded) = ImmutableImage::from_iter(
+                pixels.into_iter(),
+                Dimensions::Dim2d { width, height },
+                Format::R8G8B8A8Srgb,
+                queue.clone(),
+            )
+            .unwrap();
+            uploads.push(Box::new(uploaded) as Box<dyn GpuFuture>);
+
+            Arc::new(
+                PersistentDescriptorSet::start(texture_layout.clone())
+                    .add_sampled_image(image, sampler.clone())
+                    .unwrap()
+                    .build()
+                    .unwrap(),
+            ) as Arc<dyn DescriptorSet + Send + Sync>
+        };
+
+        // Primitives without a base color texture sample a white one.
+        let white = upload_texture(1, 1, vec![255; 4]);
+        let textures = scene
+            .textures
+            .iter()
+            .map(|texture| upload_texture(texture.width, texture.height, texture.pixels.clone()))
+            .collect::<Vec<_>>();
+
+        let (center, radius) = scene.bounding_sphere();
+
+        let draw_calls = scene
+            .primitives
+            .into_iter()
+            .map(|primitive| {
+                let (vertex_buffer, vertices_uploaded) = ImmutableBuffer::from_iter(
+                    primitive.vertices.into_iter(),
+                    BufferUsage::vertex_buffer(),
+                    queue.clone(),
+                )
+                .unwrap();
+                let (index_buffer, indices_uploaded) = ImmutableBuffer::from_iter(
+                    primitive.indices.into_iter(),
+                    BufferUsage::index_buffer(),
+                    queue.clone(),
+                )
+                .unwrap();
+                uploads
+                    .push(Box::new(vertices_uploaded.join(indices_uploaded)) as Box<dyn GpuFuture>);
+
+                DrawCall {
+                    vertex_buffer,
+                    index_buffer,
+                    texture_set: primitive
+                        .base_color_texture
+                        .map(|index| textures[index].clone())
+                        .unwrap_or_else(|| white.clone()),
+                    transform: primitive.transform,
+                    base_color: primitive.base_color,
+                }
+            })
+            .collect();
+
+        for uploaded in uploads {
+            uploaded
+                .then_signal_fence_and_flush()
+                .unwrap()
+                .wait(None)
+                .unwrap();
+        }
+
+        SceneRenderer {
+            device,
+            pipeline,
+            draw_calls,
+            camera_pool,
+            scene_camera: scene.camera.map(|camera| (camera.view, camera.projection)),
+            center,
+            radius,
+        }
+    }
+
+    /// View and projection of the scene's camera, or of `framing_camera` if it has none.
+    fn camera(&self, aspect: f32) -> (Matrix4<f32>, Matrix4<f32>) {
+        match &self.scene_camera {
+            Some((view, projection)) => {
+                let projection = match *projection {
+                    SceneProjection::Perspective { yfov, znear, zfar } => cgmath::perspective(
+                        Rad(yfov),
+                        aspect,
+                        znear,
+                        zfar.unwrap_or(znear * 10_000.0),
+                    ),
+                    SceneProjection::Orthographic {
+                        xmag,
+                        ymag,
+                        znear,
+                        zfar,
+                    } => cgmath::ortho(-xmag, xmag, -ymag, ymag, znear, zfar),
+                };
+
+                (*view, vulkan_clip() * projection)
+            }
+            None => framing_camera(self.radius, aspect),
+        }
+    }
+}
+
+impl Turntable for SceneRenderer {
+    /// Draws the scene turned around its center. A scene camera looks at the turned scene from
+    /// where it is placed, otherwise `framing_camera` looks at it.
+    fn draw(
+        &mut self,
+        builder: AutoCommandBufferBuilder,
+        dynamic_state: &DynamicState,
+        aspect: f32,
+        angle: f32,
+    ) -> AutoCommandBufferBuilder {
+        let (view, projection) = self.camera(aspect);
+        let view = if self.scene_camera.is_some() {
+            view * Matrix4::from_translation(Vector3::from(self.center))
+                * turntable(self.center, angle)
+        } else {
+            view * turntable(self.center, angle)
+        };
+
+        let camera_buffer = CpuAccessibleBuffer::from_data(
+            self.device.clone(),
+            BufferUsage::uniform_buffer(),
+            false,
+            vs_scene::ty::Camera {
+                view: view.into(),
+                projection: projection.into(),
+            },
+        )
+        .expect("failed to create camera_buffer");
+
+        let camera_set = Arc::new(
+            self.camera_pool
+                .next()
+                .add_buffer(camera_buffer)
+                .unwrap()
+                .build()
+                .unwrap(),
+        );
+
+        let mut builder = builder;
+        for draw_call in &self.draw_calls {
+            builder = builder
+                .draw_indexed(
+                    self.pipeline.clone(),
+                    dynamic_state,
+                    draw_call.vertex_buffer.clone(),
+                    draw_call.index_buffer.clone(),
+                    (camera_set.clone(), draw_call.texture_set.clone()),
+                    vs_scene::ty::Primitive {
+                        model: draw_call.transform.into(),
+                        base_color: draw_call.base_color,
+                    },
+                )
+                .unwrap();
+        }
+
+        builder
+    }
+}
+
+pub fn scene_offscreen_and_save<S: AsRef<Path>, P: AsRef<Path>>(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    scene: S,
+    samples: u32,
+    path: P,
+) {
+    let scene = Scene::load_gltf(scene).expect("failed to load scene");
+    render_scene(device, queue, scene, [1024, 1024], samples)
+        .save(path)
+        .unwrap();
+}
+
+/// Renders the scene from its camera into an sRGB image of `dimensions`.
+pub fn render_scene(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    scene: Scene,
+    dimensions: [u32; 2],
+    samples: u32,
+) -> Pixels {
+    render_turntable(
+        device.clone(),
+        queue.clone(),
+        dimensions,
+        Format::R8G8B8A8Srgb,
+        samples,
+        0.0,
+        |render_pass| SceneRenderer::new(device.clone(), queue.clone(), render_pass, scene),
+    )
+}
+
+/// Shows the scene slowly turning in a window.
+pub fn scene_window<S: AsRef<Path>>(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    instance: Arc<Instance>,
+    scene: S,
+    samples: u32,
+    present_mode: PresentMode,
+) {
+    let scene = Scene::load_gltf(scene).expect("failed to load scene");
+
+    turntable_window(
+        device.clone(),
+        queue.clone(),
+        instance,
+        samples,
+        present_mode,
+        move |render_pass| SceneRenderer::new(device, queue, render_pass, scene),
+    );
+}
+
+mod vs_scene {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        path: "shaders/scene.vert.glsl"
+    }
+}
+
+mod fs_scene {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "shaders/scene.frag.glsl"
+    }
+}
@@ -0,0 +1,54 @@
+use crate::examples::readback::ReadbackBuffer;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use vulkano::command_buffer::AutoCommandBufferBuilder;
+use vulkano::device::Device;
+use vulkano::image::SwapchainImage;
+use winit::window::Window;
+
+/// A copy of the swapchain image a frame renders to, saved as a PNG once the frame is done.
+///
+/// Record it after the render pass with `copy_from`, then call `save` after waiting for the
+/// fence of the frame. The readback keeps the swapchain format, so BGRA images are swizzled and
+/// sRGB images keep their encoded bytes just like the window shows them.
+pub struct Screenshot {
+    readback: ReadbackBuffer,
+}
+
+impl Screenshot {
+    pub fn new(device: Arc<Device>, image: &Arc<SwapchainImage<Window>>) -> Screenshot {
+        Screenshot {
+            readback: ReadbackBuffer::new(device, image.dimensions(), image.swapchain().format()),
+        }
+    }
+
+    /// Appends a copy of `image` to `builder`.
+    pub fn copy_from(
+        &self,
+        builder: AutoCommandBufferBuilder,
+        image: Arc<SwapchainImage<Window>>,
+    ) -> AutoCommandBufferBuilder {
+        self.readback.copy_from(builder, image)
+    }
+
+    /// Saves to a file in the working directory named after the current time.
+    pub fn save(&self) {
+        let path = screenshot_path();
+
+        match self.readback.pixels().save(&path) {
+            Ok(()) => println!("Saved screenshot to {}", path.display()),
+            Err(e) => println!("Failed to save screenshot: {}", e),
+        }
+    }
+}
+
+/// `screenshot-<milliseconds since the epoch>.png`, so that consecutive screenshots sort by time.
+pub fn screenshot_path() -> PathBuf {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+
+    PathBuf::from(format!("screenshot-{}.png", millis))
+}
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+use vulkano::device::{Device, Queue};
+use vulkano::format::Format;
+use vulkano::image::SwapchainImage;
+use vulkano::swapchain::{
+    Capabilities, ColorSpace, FullscreenExclusive, PresentMode, SupportedPresentModes, Surface,
+    SurfaceTransform, Swapchain, SwapchainCreationError,
+};
+use winit::window::Window;
+
+/// Parses `fifo`, `relaxed`, `mailbox` or `immediate`.
+pub fn parse_present_mode(name: &str) -> Option<PresentMode> {
+    match name {
+        "fifo" => Some(PresentMode::Fifo),
+        "relaxed" => Some(PresentMode::Relaxed),
+        "mailbox" => Some(PresentMode::Mailbox),
+        "immediate" => Some(PresentMode::Immediate),
+        _ => None,
+    }
+}
+
+/// `requested` if the surface supports it, otherwise the closest mode it does support. Fifo is
+/// the last resort, every surface supports it.
+pub fn choose_present_mode(
+    supported: SupportedPresentModes,
+    requested: PresentMode,
+) -> PresentMode {
+    let fallbacks: &[PresentMode] = match requested {
+        PresentMode::Immediate => &[PresentMode::Immediate, PresentMode::Mailbox],
+        PresentMode::Mailbox => &[PresentMode::Mailbox, PresentMode::Immediate],
+        PresentMode::Relaxed => &[PresentMode::Relaxed],
+        PresentMode::Fifo => &[],
+    };
+
+    let mode = fallbacks
+        .iter()
+        .cloned()
+        .find(|&mode| supported.supports(mode))
+        .unwrap_or(PresentMode::Fifo);
+
+    if mode != requested {
+        println!(
+            "Present mode {:?} is not supported, using {:?} instead",
+            requested, mode
+        );
+    }
+
+    mode
+}
+
+/// Whether `mode` waits for vertical blank.
+pub fn is_vsync(mode: PresentMode) -> bool {
+    match mode {
+        PresentMode::Fifo | PresentMode::Relaxed => true,
+        PresentMode::Mailbox | PresentMode::Immediate => false,
+    }
+}
+
+/// The present mode that switches vsync of `mode` on or off.
+pub fn toggle_vsync(supported: SupportedPresentModes, mode: PresentMode) -> PresentMode {
+    if is_vsync(mode) {
+        choose_present_mode(supported, PresentMode::Immediate)
+    } else {
+        PresentMode::Fifo
+    }
+}
+
+/// An sRGB format in the sRGB color space if the surface offers one, so that shaders can write
+/// linear colors, otherwise the first format it offers.
+pub fn choose_surface_format(caps: &Capabilities) -> (Format, ColorSpace) {
+    caps.supported_formats
+        .iter()
+        .cloned()
+        .find(|&(format, color_space)| {
+            color_space == ColorSpace::SrgbNonLinear
+                && (format == Format::B8G8R8A8Srgb || format == Format::R8G8B8A8Srgb)
+        })
+        .unwrap_or(caps.supported_formats[0])
+}
+
+type WindowSwapchain = (Arc<Swapchain<Window>>, Vec<Arc<SwapchainImage<Window>>>);
+
+/// Creates a swapchain for the whole window with `present_mode`, replacing `old` if given. The
+/// surface has to support `present_mode`, the caller picks it with `choose_present_mode`.
+///
+/// Fails with `UnsupportedDimensions` while the window is minimized.
+pub fn create_swapchain(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    surface: Arc<Surface<Window>>,
+    present_mode: PresentMode,
+    old: Option<Arc<Swapchain<Window>>>,
+) -> Result<WindowSwapchain, SwapchainCreationError> {
+    let caps = surface
+        .capabilities(device.physical_device())
+        .expect("failed to get surface capabilities");
+
+    let dimensions = caps
+        .current_extent
+        .unwrap_or_else(|| surface.window().inner_size().into());
+    let alpha = caps.supported_composite_alpha.iter().next().unwrap();
+    let (format, color_space) = choose_surface_format(&caps);
+
+    println!(
+        "Swapchain format {:?}, present mode {:?}",
+        format, present_mode
+    );
+
+    match old {
+        None => Swapchain::new(
+            device.clone(),
+            surface.clone(),
+            caps.min_image_count,
+            format,
+            dimensions,
+            1,
+            caps.supported_usage_flags,
+            &queue,
+            SurfaceTransform::Identity,
+            alpha,
+            present_mode,
+            FullscreenExclusive::Default,
+            true,
+            color_space,
+        ),
+        Some(old) => Swapchain::with_old_swapchain(
+            device.clone(),
+            surface.clone(),
+            caps.min_image_count,
+            format,
+            dimensions,
+            1,
+            caps.supported_usage_flags,
+            &queue,
+            SurfaceTransform::Identity,
+            alpha,
+            present_mode,
+            FullscreenExclusive::Default,
+            true,
+            color_space,
+            old,
+        ),
+    }
+}
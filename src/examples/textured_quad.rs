@@ -0,0 +1,276 @@
+use crate::examples::mesh_renderer::{render_turntable, turntable_window, Turntable};
+use crate::examples::readback::Pixels;
+use std::path::Path;
+use std::sync::Arc;
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer, ImmutableBuffer};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, DynamicState};
+use vulkano::descriptor::descriptor_set::{DescriptorSet, PersistentDescriptorSet};
+use vulkano::descriptor::PipelineLayoutAbstract;
+use vulkano::device::{Device, Queue};
+use vulkano::format::Format;
+use vulkano::framebuffer::{RenderPassAbstract, Subpass};
+use vulkano::image::{Dimensions, ImmutableImage};
+use vulkano::instance::Instance;
+use vulkano::pipeline::{GraphicsPipeline, GraphicsPipelineAbstract};
+use vulkano::sampler::{BorderColor, Filter, MipmapMode, Sampler, SamplerAddressMode};
+use vulkano::swapchain::PresentMode;
+use vulkano::sync::GpuFuture;
+
+/// Angle the offscreen render turns the quad by, so that filtering shows along its edges.
+const OFFSCREEN_ANGLE: f32 = 0.3;
+
+/// How the quad's texture is sampled.
+#[derive(Copy, Clone)]
+pub struct SamplerOptions {
+    pub filter: Filter,
+    pub address_mode: SamplerAddressMode,
+    /// How often the texture fits across the quad, values above one exercise `address_mode`.
+    pub repeat: f32,
+}
+
+impl SamplerOptions {
+    /// Parses `nearest` or `linear` and `repeat`, `mirror`, `clamp` or `border`.
+    pub fn parse(filter: &str, address_mode: &str, repeat: f32) -> Option<SamplerOptions> {
+        Some(SamplerOptions {
+            filter: match filter {
+                "nearest" => Filter::Nearest,
+                "linear" => Filter::Linear,
+                _ => return None,
+            },
+            address_mode: match address_mode {
+                "repeat" => SamplerAddressMode::Repeat,
+                "mirror" => SamplerAddressMode::MirroredRepeat,
+                "clamp" => SamplerAddressMode::ClampToEdge,
+                "border" => SamplerAddressMode::ClampToBorder(BorderColor::FloatOpaqueBlack),
+                _ => return None,
+            },
+            repeat,
+        })
+    }
+
+    fn sampler(&self, device: Arc<Device>) -> Arc<Sampler> {
+        Sampler::new(
+            device,
+            self.filter,
+            self.filter,
+            MipmapMode::Nearest,
+            self.address_mode,
+            self.address_mode,
+            self.address_mode,
+            0.0,
+            1.0,
+            0.0,
+            0.0,
+        )
+        .expect("failed to create sampler")
+    }
+}
+
+#[derive(Default, Copy, Clone)]
+struct QuadVertex {
+    position: [f32; 2],
+    tex_coord: [f32; 2],
+}
+
+vulkano::impl_vertex!(QuadVertex, position, tex_coord);
+
+impl QuadVertex {
+    fn new(position: [f32; 2], tex_coord: [f32; 2]) -> QuadVertex {
+        QuadVertex {
+            position,
+            tex_coord,
+        }
+    }
+}
+
+/// Draws an image on a quad that keeps its aspect ratio and turns in the viewport.
+pub struct TexturedQuad {
+    pipeline: Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
+    vertex_buffer: Arc<ImmutableBuffer<[QuadVertex]>>,
+    texture_set: Arc<dyn DescriptorSet + Send + Sync>,
+    image_aspect: f32,
+    repeat: f32,
+}
+
+impl TexturedQuad {
+    /// Uploads the image at `path` through a staging buffer.
+    pub fn new<P: AsRef<Path>>(
+        device: Arc<Device>,
+        queue: Arc<Queue>,
+        render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
+        path: P,
+        options: SamplerOptions,
+    ) -> TexturedQuad {
+        let image = image::open(path)
+            .expect("failed to load texture")
+            .to_rgba8();
+        let (width, height) = image.dimensions();
+
+        let staging_buffer = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::transfer_source(),
+            false,
+            image.into_raw().into_iter(),
+        )
+        .unwrap();
+
+        let (texture, texture_uploaded) = ImmutableImage::from_buffer(
+            staging_buffer,
+            Dimensions::Dim2d { width, height },
+            Format::R8G8B8A8Srgb,
+            queue.clone(),
+        )
+        .unwrap();
+
+        let (vertex_buffer, vertices_uploaded) = ImmutableBuffer::from_iter(
+            vec![
+                QuadVertex::new([-1.0, -1.0], [0.0, 0.0]),
+                QuadVertex::new([-1.0, 1.0], [0.0, 1.0]),
+                QuadVertex::new([1.0, -1.0], [1.0, 0.0]),
+                QuadVertex::new([1.0, 1.0], [1.0, 1.0]),
+            ]
+            .into_iter(),
+            BufferUsage::vertex_buffer(),
+            queue.clone(),
+        )
+        .unwrap();
+
+        texture_uploaded
+            .join(vertices_uploaded)
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        let vert_shader =
+            vs_textured::Shader::load(device.clone()).expect("failed to create vert_shader");
+        let frag_shader =
+            fs_textured::Shader::load(device.clone()).expect("failed to create frag_shader");
+
+        let pipeline = Arc::new(
+            GraphicsPipeline::start()
+                .vertex_input_single_buffer::<QuadVertex>()
+                .vertex_shader(vert_shader.main_entry_point(), ())
+                .triangle_strip()
+                .viewports_dynamic_scissors_irrelevant(1)
+                .fragment_shader(frag_shader.main_entry_point(), ())
+                .render_pass(Subpass::from(render_pass, 0).unwrap())
+                .build(device.clone())
+                .unwrap(),
+        );
+
+        let texture_layout = pipeline.descriptor_set_layout(0).unwrap();
+        let texture_set = Arc::new(
+            PersistentDescriptorSet::start(texture_layout.clone())
+                .add_sampled_image(texture, options.sampler(device.clone()))
+                .unwrap()
+                .build()
+                .unwrap(),
+        );
+
+        TexturedQuad {
+            pipeline,
+            vertex_buffer,
+            texture_set,
+            image_aspect: width as f32 / height as f32,
+            repeat: options.repeat,
+        }
+    }
+}
+
+impl Turntable for TexturedQuad {
+    /// Draws the quad turned by `angle` in the plane of the viewport, covering most of it.
+    fn draw(
+        &mut self,
+        builder: AutoCommandBufferBuilder,
+        dynamic_state: &DynamicState,
+        aspect: f32,
+        angle: f32,
+    ) -> AutoCommandBufferBuilder {
+        let fit = 0.8 * (aspect / self.image_aspect).min(1.0);
+
+        builder
+            .draw(
+                self.pipeline.clone(),
+                dynamic_state,
+                self.vertex_buffer.clone(),
+                self.texture_set.clone(),
+                vs_textured::ty::Quad {
+                    size: [fit * self.image_aspect, fit],
+                    tex_coord_scale: [self.repeat, self.repeat],
+                    angle,
+                    aspect,
+                },
+            )
+            .unwrap()
+    }
+}
+
+pub fn textured_quad_and_save<T: AsRef<Path>, P: AsRef<Path>>(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    texture: T,
+    options: SamplerOptions,
+    samples: u32,
+    path: P,
+) {
+    render_textured_quad(device, queue, texture, options, [1024, 1024], samples)
+        .save(path)
+        .unwrap();
+}
+
+/// Renders the image at `texture` on a quad into an sRGB image of `dimensions`.
+pub fn render_textured_quad<T: AsRef<Path>>(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    texture: T,
+    options: SamplerOptions,
+    dimensions: [u32; 2],
+    samples: u32,
+) -> Pixels {
+    render_turntable(
+        device.clone(),
+        queue.clone(),
+        dimensions,
+        Format::R8G8B8A8Srgb,
+        samples,
+        OFFSCREEN_ANGLE,
+        |render_pass| {
+            TexturedQuad::new(device.clone(), queue.clone(), render_pass, texture, options)
+        },
+    )
+}
+
+/// Shows the image at `texture` on a slowly turning quad in a window.
+pub fn textured_quad_window<T: AsRef<Path>>(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    instance: Arc<Instance>,
+    texture: T,
+    options: SamplerOptions,
+    samples: u32,
+    present_mode: PresentMode,
+) {
+    turntable_window(
+        device.clone(),
+        queue.clone(),
+        instance,
+        samples,
+        present_mode,
+        move |render_pass| TexturedQuad::new(device, queue, render_pass, texture, options),
+    );
+}
+
+mod vs_textured {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        path: "shaders/textured.vert.glsl"
+    }
+}
+
+mod fs_textured {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "shaders/textured.frag.glsl"
+    }
+}
@@ -1,98 +1,477 @@
-use rand::Rng;
+use crate::examples::app::{run_app, App, AppContext, AppOptions, FrameInfo};
+use crate::examples::attractors::{Attractor, Attractors, GpuAttractor, MAX_ATTRACTORS};
+use crate::examples::camera::Camera2d;
+use crate::examples::gui::{present_mode_buttons, Gui};
+use crate::examples::hud::Hud;
+use crate::examples::nbody::{EnergyReadout, NBody, NBodyParams, Solver, MAX_DIRECT_BODIES};
 use std::sync::Arc;
-use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer, DeviceLocalBuffer, TypedBufferAccess};
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer, DeviceLocalBuffer};
 use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBuffer, DynamicState};
-use vulkano::descriptor::descriptor_set::{FixedSizeDescriptorSetsPool, PersistentDescriptorSet};
+use vulkano::descriptor::descriptor_set::{DescriptorSet, PersistentDescriptorSet};
 use vulkano::descriptor::PipelineLayoutAbstract;
 use vulkano::device::{Device, Queue};
-use vulkano::framebuffer::{Framebuffer, FramebufferAbstract, RenderPassAbstract, Subpass};
-use vulkano::image::SwapchainImage;
-use vulkano::instance::Instance;
-use vulkano::pipeline::viewport::Viewport;
-use vulkano::pipeline::{ComputePipeline, GraphicsPipeline};
-use vulkano::swapchain::{
-    self, AcquireError, ColorSpace, FullscreenExclusive, PresentMode, SurfaceTransform, Swapchain,
-    SwapchainCreationError,
+use vulkano::framebuffer::Subpass;
+use vulkano::instance::{Instance, PhysicalDevice};
+use vulkano::pipeline::{
+    ComputePipeline, ComputePipelineAbstract, GraphicsPipeline, GraphicsPipelineAbstract,
+};
+use vulkano::swapchain::PresentMode;
+use vulkano::sync::GpuFuture;
+use winit::event::{
+    ElementState, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent,
 };
-use vulkano::sync;
-use vulkano::sync::{FlushError, GpuFuture};
-use vulkano_win::VkSurfaceBuild;
-use winit::event_loop::{ControlFlow, EventLoop};
-use winit::window::{Fullscreen, Window, WindowBuilder};
-
-pub fn graphics_window(device: Arc<Device>, queue: Arc<Queue>, instance: Arc<Instance>) {
-    let events_loop = EventLoop::new();
-
-    // let fullscreen = Fullscreen::Exclusive(
-    //     events_loop
-    //         .primary_monitor()
-    //         .video_modes()
-    //         .next()
-    //         .expect("failed to get video mode for exclusive fullscreen"),
-    // );
-
-    // let fullscreen = Fullscreen::Borderless(events_loop.primary_monitor());
-
-    let surface = WindowBuilder::new()
-        // .with_fullscreen(Some(fullscreen))
-        .build_vk_surface(&events_loop, instance.clone())
-        .unwrap();
 
-    let caps = surface
-        .capabilities(device.physical_device())
-        .expect("failed to get surface capabilities");
+/// Particles the physics shader processes per workgroup.
+const WORKGROUP_SIZE: u32 = 1024;
+
+/// The smallest workgroup of any pass over all particles, the N-body tiles and the Barnes-Hut
+/// sort and force passes. It sets how many particles fit into one dispatch.
+const NARROWEST_WORKGROUP_SIZE: u32 = 256;
+
+/// Zoom factor per wheel line.
+const ZOOM_STEP: f32 = 1.1;
+
+/// Where the particles start out and how they move at first.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Distribution {
+    /// Uniform over the initial view, at rest.
+    Square,
+    /// Uniform over the unit disc, at rest.
+    Disc,
+    /// A few Gaussian blobs at seeded places, at rest.
+    Clusters,
+    /// A disc that gets denser towards the center, orbiting it.
+    Galaxy,
+    /// A thin ring, at rest.
+    Ring,
+    /// Three spiral arms, orbiting the center.
+    Spiral,
+    /// A regular grid over the initial view, at rest.
+    Grid,
+}
 
-    let dimensions = caps.current_extent.unwrap_or([1280, 1024]);
-    let alpha = caps.supported_composite_alpha.iter().next().unwrap();
-    let format = caps.supported_formats[0].0;
+impl Distribution {
+    pub const ALL: [Distribution; 7] = [
+        Distribution::Square,
+        Distribution::Disc,
+        Distribution::Clusters,
+        Distribution::Galaxy,
+        Distribution::Ring,
+        Distribution::Spiral,
+        Distribution::Grid,
+    ];
+
+    /// Parses `square`, `disc`, `clusters`, `galaxy`, `ring`, `spiral` or `grid`.
+    pub fn parse(name: &str) -> Option<Distribution> {
+        Distribution::ALL
+            .iter()
+            .cloned()
+            .find(|distribution| distribution.name() == name)
+    }
 
-    let (mut swapchain, images) = Swapchain::new(
-        device.clone(),
-        surface.clone(),
-        caps.min_image_count,
-        format,
-        dimensions,
-        1,
-        caps.supported_usage_flags,
-        &queue,
-        SurfaceTransform::Identity,
-        alpha,
-        PresentMode::Fifo,
-        FullscreenExclusive::Default,
-        true,
-        ColorSpace::SrgbNonLinear,
-    )
-    .expect("failed to create swapchain");
+    pub fn name(self) -> &'static str {
+        match self {
+            Distribution::Square => "square",
+            Distribution::Disc => "disc",
+            Distribution::Clusters => "clusters",
+            Distribution::Galaxy => "galaxy",
+            Distribution::Ring => "ring",
+            Distribution::Spiral => "spiral",
+            Distribution::Grid => "grid",
+        }
+    }
+}
 
-    let mut particles = Vec::new();
+/// How the particles are spawned at startup.
+#[derive(Copy, Clone, Debug)]
+pub struct ParticleOptions {
+    pub particle_count: u32,
+    /// The same seed always spawns the same particles.
+    pub seed: u32,
+    pub distribution: Distribution,
+}
 
-    const PARTICLE_COUNT: u32 = 1_048_576;
+/// The most particles `physical` can dispatch every pass for and bind as one storage buffer.
+pub fn max_particle_count(physical: PhysicalDevice) -> u32 {
+    let limits = physical.limits();
+    let dispatchable =
+        limits.max_compute_work_group_count()[0].saturating_mul(NARROWEST_WORKGROUP_SIZE);
+    let bindable = limits.max_storage_buffer_range() / std::mem::size_of::<Vertex>() as u32;
+    dispatchable.min(bindable)
+}
 
-    println!("Generating particles");
-    for i in 1..PARTICLE_COUNT {
-        particles.push(Vertex::new(
-            rand::thread_rng().gen_range(-1.0, 1.0),
-            rand::thread_rng().gen_range(-1.0, 1.0),
-        ));
+/// What moves the particles.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Simulation {
+    /// The cursor and the placed attractors pull, the particles ignore each other.
+    Attractors,
+    /// Every particle pulls every other one, the attractors are ignored.
+    NBody,
+}
+
+/// What the settings panel edits.
+#[derive(Copy, Clone, Debug)]
+struct ParticleSettings {
+    simulation: Simulation,
+    /// Mass of the attractor following the cursor and of newly placed ones.
+    target_mass: f32,
+    follow_cursor: bool,
+    /// Fraction of the velocity particles keep per step, before the time step is applied.
+    damping: f32,
+    /// Simulated seconds per real second.
+    time_scale: f32,
+    /// Takes effect on the next respawn.
+    spawn: ParticleOptions,
+    slow_color: [f32; 3],
+    fast_color: [f32; 3],
+    /// Gravitational constant of the N-body simulation, all particles together weigh 1.
+    gravity: f32,
+    softening: f32,
+    solver: Solver,
+    /// Opening angle of the Barnes-Hut solver.
+    theta: f32,
+    measure_energy: bool,
+}
+
+impl ParticleSettings {
+    fn new(spawn: ParticleOptions) -> ParticleSettings {
+        ParticleSettings {
+            simulation: Simulation::Attractors,
+            target_mass: 1.0,
+            follow_cursor: true,
+            damping: 0.999,
+            time_scale: 1.0,
+            spawn,
+            slow_color: [1.0, 1.0, 1.0],
+            fast_color: [1.0, 1.0, 1.0],
+            gravity: 1.0,
+            softening: 0.02,
+            solver: Solver::Direct,
+            theta: 0.5,
+            measure_energy: true,
+        }
     }
-    println!("Done. Enjoy");
+}
 
-    let cpu_buffer = CpuAccessibleBuffer::from_iter(
-        device.clone(),
-        BufferUsage {
-            transfer_source: true,
-            ..BufferUsage::none()
-        },
-        false,
-        particles.into_iter(),
-    )
-    .unwrap();
+/// Particles pulled towards the mouse cursor and placed attractors, or towards each other, by
+/// compute shaders and drawn as points.
+struct ParticlesApp {
+    queue: Arc<Queue>,
+    pipeline: Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
+    particle_compute_pipeline: Arc<dyn ComputePipelineAbstract + Send + Sync>,
+    particle_init_pipeline: Arc<dyn ComputePipelineAbstract + Send + Sync>,
+    vertex_buffer: Arc<DeviceLocalBuffer<[Vertex]>>,
+    particle_set: Arc<dyn DescriptorSet + Send + Sync>,
+    particle_count: u32,
+    /// Respawns are clamped to this, see `max_particle_count`.
+    max_particles: u32,
+    frames: Vec<Frame>,
+    hud: Hud,
+    gui: Gui,
+    settings: ParticleSettings,
+    frames_in_flight: usize,
+    present_mode: PresentMode,
+    requested_present_mode: Option<PresentMode>,
+    dimensions: [u32; 2],
+    camera: Camera2d,
+    /// Cursor position in pixels.
+    cursor: [f32; 2],
+    panning: bool,
+    attractors: Attractors,
+    nbody: NBody,
+    energy: Option<EnergyReadout>,
+    /// Total energy of the first readout since the particles or the constants last changed.
+    initial_energy: Option<f64>,
+    delta_time: f32,
+}
+
+impl ParticlesApp {
+    fn new(context: &AppContext, options: ParticleOptions) -> ParticlesApp {
+        let device = context.device.clone();
+        let queue = context.queue.clone();
+
+        let init_shader =
+            cs_particle_init::Shader::load(device.clone()).expect("failed to load init_shader");
+
+        let particle_init_pipeline: Arc<dyn ComputePipelineAbstract + Send + Sync> = Arc::new(
+            ComputePipeline::new(device.clone(), &init_shader.main_entry_point(), &())
+                .expect("failed to create particle_init_pipeline"),
+        );
+
+        let settings = ParticleSettings::new(options);
+        let vertex_buffer = spawn_particles(queue.clone(), &particle_init_pipeline, options);
+
+        let hud = Hud::new(device.clone(), queue.clone(), context.render_pass.clone());
+        let gui = Gui::new(device.clone(), queue.clone(), context.render_pass.clone());
+
+        let vert_shader =
+            vs_graphics::Shader::load(device.clone()).expect("failed to create vert_shader");
+        let frag_shader =
+            fs_graphics::Shader::load(device.clone()).expect("failed to create frag_shader");
+
+        let pipeline = Arc::new(
+            GraphicsPipeline::start()
+                .polygon_mode_point()
+                .vertex_input_single_buffer::<Vertex>()
+                .vertex_shader(vert_shader.main_entry_point(), ())
+                .viewports_dynamic_scissors_irrelevant(1)
+                .fragment_shader(frag_shader.main_entry_point(), ())
+                .render_pass(Subpass::from(context.render_pass.clone(), 0).unwrap())
+                .build(device.clone())
+                .unwrap(),
+        );
+
+        let vertex_uniform_layout = pipeline.layout().descriptor_set_layout(0).unwrap();
+
+        let particle_shader = cs_particle_physics::Shader::load(device.clone())
+            .expect("failed to load particle_shader");
+
+        let particle_compute_pipeline: Arc<dyn ComputePipelineAbstract + Send + Sync> = Arc::new(
+            ComputePipeline::new(device.clone(), &particle_shader.main_entry_point(), &())
+                .expect("failed to create particle_compute_pipeline"),
+        );
+
+        let uniform_layout = particle_compute_pipeline
+            .layout()
+            .descriptor_set_layout(1)
+            .unwrap();
+
+        let particle_set = particle_set(&particle_compute_pipeline, vertex_buffer.clone());
+        let nbody = NBody::new(
+            queue.clone(),
+            vertex_buffer.clone(),
+            options.particle_count,
+            context.frames_in_flight,
+        );
+
+        let frames = (0..context.frames_in_flight)
+            .map(|_| {
+                let particle_uniforms = CpuAccessibleBuffer::from_data(
+                    device.clone(),
+                    BufferUsage::uniform_buffer(),
+                    false,
+                    ParticleUBO {
+                        delta_time: 0.0,
+                        damping: 0.0,
+                        attractor_count: 0,
+                    },
+                )
+                .expect("failed to create particle_uniforms");
+                let attractors = CpuAccessibleBuffer::from_iter(
+                    device.clone(),
+                    BufferUsage {
+                        storage_buffer: true,
+                        ..BufferUsage::none()
+                    },
+                    false,
+                    (0..MAX_ATTRACTORS).map(|_| GpuAttractor::default()),
+                )
+                .expect("failed to create attractors");
+                let vertex_uniforms = CpuAccessibleBuffer::from_data(
+                    device.clone(),
+                    BufferUsage::uniform_buffer(),
+                    false,
+                    VertexUBO {
+                        view: Camera2d::new(1.0).view_matrix().into(),
+                        slow_color: [1.0; 4],
+                        fast_color: [1.0; 4],
+                    },
+                )
+                .expect("failed to create vertex_uniforms");
+
+                Frame {
+                    particle_uniform_set: Arc::new(
+                        PersistentDescriptorSet::start(uniform_layout.clone())
+                            .add_buffer(particle_uniforms.clone())
+                            .unwrap()
+                            .add_buffer(attractors.clone())
+                            .unwrap()
+                            .build()
+                            .unwrap(),
+                    ),
+                    vertex_uniform_set: Arc::new(
+                        PersistentDescriptorSet::start(vertex_uniform_layout.clone())
+                            .add_buffer(vertex_uniforms.clone())
+                            .unwrap()
+                            .build()
+                            .unwrap(),
+                    ),
+                    particle_uniforms,
+                    attractors,
+                    vertex_uniforms,
+                }
+            })
+            .collect();
+
+        ParticlesApp {
+            queue,
+            pipeline,
+            particle_compute_pipeline,
+            particle_init_pipeline,
+            vertex_buffer,
+            particle_set,
+            particle_count: options.particle_count,
+            max_particles: max_particle_count(device.physical_device()),
+            frames,
+            hud,
+            gui,
+            settings,
+            frames_in_flight: context.frames_in_flight,
+            present_mode: PresentMode::Fifo,
+            requested_present_mode: None,
+            dimensions: [1, 1],
+            camera: Camera2d::new(1.0),
+            cursor: [0.0, 0.0],
+            panning: false,
+            attractors: Attractors::default(),
+            nbody,
+            energy: None,
+            initial_energy: None,
+            delta_time: 0.0,
+        }
+    }
+
+    /// Replaces every particle with new ones as `settings.spawn` says.
+    fn respawn(&mut self) {
+        let spawn = &mut self.settings.spawn;
+        spawn.particle_count = spawn.particle_count.min(self.max_particles);
+        self.particle_count = spawn.particle_count;
+        self.vertex_buffer = spawn_particles(
+            self.queue.clone(),
+            &self.particle_init_pipeline,
+            self.settings.spawn,
+        );
+        self.particle_set =
+            particle_set(&self.particle_compute_pipeline, self.vertex_buffer.clone());
+        self.nbody
+            .set_particles(self.vertex_buffer.clone(), self.particle_count);
+        self.energy = None;
+        self.initial_energy = None;
+    }
+}
+
+/// The settings panel, returns whether the particles should be respawned.
+fn settings_window(
+    ctx: &egui::CtxRef,
+    settings: &mut ParticleSettings,
+    attractors: &mut Attractors,
+    present_mode: &mut PresentMode,
+    particle_count: u32,
+    energy: Option<EnergyReadout>,
+    initial_energy: Option<f64>,
+) -> bool {
+    let mut respawn = false;
+
+    egui::Window::new("Settings")
+        .default_pos([8.0, 210.0])
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.radio_value(
+                    &mut settings.simulation,
+                    Simulation::Attractors,
+                    "attractors",
+                );
+                ui.radio_value(&mut settings.simulation, Simulation::NBody, "n-body");
+            });
+            ui.horizontal(|ui| {
+                ui.add(egui::Slider::new(&mut settings.target_mass, -5.0..=5.0).text("mass"));
+                ui.checkbox(&mut settings.follow_cursor, "follow cursor");
+            });
+            ui.add(egui::Slider::new(&mut settings.damping, 0.9..=1.0).text("damping"));
+            ui.add(egui::Slider::new(&mut settings.time_scale, 0.0..=4.0).text("time scale"));
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::Slider::new(&mut settings.spawn.particle_count, 1..=16_777_216)
+                        .logarithmic(true)
+                        .text("particles"),
+                );
+                ui.add(egui::DragValue::new(&mut settings.spawn.seed).prefix("seed: "));
+            });
+            ui.horizontal_wrapped(|ui| {
+                for &distribution in &Distribution::ALL {
+                    ui.radio_value(
+                        &mut settings.spawn.distribution,
+                        distribution,
+                        distribution.name(),
+                    );
+                }
+            });
+            respawn = ui.button("Respawn (Space)").clicked();
+            ui.horizontal(|ui| {
+                ui.label("slow");
+                ui.color_edit_button_rgb(&mut settings.slow_color);
+                ui.label("fast");
+                ui.color_edit_button_rgb(&mut settings.fast_color);
+            });
+            present_mode_buttons(ui, present_mode);
+            ui.collapsing(format!("Attractors ({})", attractors.list.len()), |ui| {
+                attractors.editor(ui);
+                if ui.button("Remove all").clicked() {
+                    attractors.clear();
+                }
+            });
+            ui.collapsing("N-body", |ui| {
+                ui.add(
+                    egui::Slider::new(&mut settings.gravity, 0.01..=100.0)
+                        .logarithmic(true)
+                        .text("gravity"),
+                );
+                ui.add(
+                    egui::Slider::new(&mut settings.softening, 0.001..=0.2)
+                        .logarithmic(true)
+                        .text("softening"),
+                );
+                if particle_count <= MAX_DIRECT_BODIES {
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut settings.solver, Solver::Direct, "direct");
+                        ui.radio_value(&mut settings.solver, Solver::BarnesHut, "barnes-hut");
+                    });
+                } else {
+                    // NBody falls back on its own, this only keeps the panel honest.
+                    settings.solver = Solver::BarnesHut;
+                    ui.label(format!(
+                        "barnes-hut, direct takes {} particles at most",
+                        MAX_DIRECT_BODIES
+                    ));
+                }
+                if settings.solver == Solver::BarnesHut {
+                    ui.add(egui::Slider::new(&mut settings.theta, 0.0..=1.5).text("theta"));
+                }
+                ui.checkbox(&mut settings.measure_energy, "measure energy");
+                if let Some(energy) = energy {
+                    ui.label(format!(
+                        "kinetic {:.6}  potential {:.6}",
+                        energy.kinetic, energy.potential
+                    ));
+                    let drift = initial_energy
+                        .map(|initial| (energy.total() - initial) / initial.abs())
+                        .unwrap_or(0.0);
+                    ui.label(format!("total {:.6}  drift {:+.2e}", energy.total(), drift));
+                    ui.label(format!(
+                        "momentum ({:+.2e}, {:+.2e})",
+                        energy.momentum[0], energy.momentum[1]
+                    ));
+                }
+            });
+        });
+
+    respawn
+}
+
+/// Allocates the particles and places them as `options.distribution` says on the GPU.
+fn spawn_particles(
+    queue: Arc<Queue>,
+    init_pipeline: &Arc<dyn ComputePipelineAbstract + Send + Sync>,
+    options: ParticleOptions,
+) -> Arc<DeviceLocalBuffer<[Vertex]>> {
+    let device = queue.device().clone();
+    let count = options.particle_count;
 
     let vertex_buffer = DeviceLocalBuffer::array(
         device.clone(),
-        cpu_buffer.len(),
+        count as usize,
         BufferUsage {
-            transfer_destination: true,
             vertex_buffer: true,
             storage_buffer: true,
             ..BufferUsage::none()
@@ -101,9 +480,17 @@ pub fn graphics_window(device: Arc<Device>, queue: Arc<Queue>, instance: Arc<Ins
     )
     .unwrap();
 
-    AutoCommandBufferBuilder::new(device.clone(), queue.family())
+    AutoCommandBufferBuilder::new(device, queue.family())
         .unwrap()
-        .copy_buffer(cpu_buffer.clone(), vertex_buffer.clone())
+        .dispatch(
+            [(count + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE, 1, 1],
+            init_pipeline.clone(),
+            particle_set(init_pipeline, vertex_buffer.clone()),
+            cs_particle_init::ty::Params {
+                seed: options.seed,
+                distribution: options.distribution as u32,
+            },
+        )
         .unwrap()
         .build()
         .unwrap()
@@ -114,339 +501,353 @@ pub fn graphics_window(device: Arc<Device>, queue: Arc<Queue>, instance: Arc<Ins
         .wait(None)
         .unwrap();
 
-    let render_pass = Arc::new(
-        vulkano::single_pass_renderpass!(device.clone(),
-            attachments: {
-                color: {
-                    load: Clear,
-                    store: Store,
-                    format: swapchain.format(),
-                    samples: 1,
-                }
-            },
-            pass: {
-                color: [color],
-                depth_stencil: {}
-            }
-        )
-        .unwrap(),
-    );
-
-    let mut dynamic_state = DynamicState::none();
-
-    let mut framebuffers =
-        window_size_dependent_setup(&images, render_pass.clone(), &mut dynamic_state);
-
-    let vert_shader =
-        vs_graphics::Shader::load(device.clone()).expect("failed to create vert_shader");
-    let frag_shader =
-        fs_graphics::Shader::load(device.clone()).expect("failed to create frag_shader");
-
-    let pipeline = Arc::new(
-        GraphicsPipeline::start()
-            .polygon_mode_point()
-            .vertex_input_single_buffer::<Vertex>()
-            .vertex_shader(vert_shader.main_entry_point(), ())
-            .viewports_dynamic_scissors_irrelevant(1)
-            .fragment_shader(frag_shader.main_entry_point(), ())
-            .render_pass(Subpass::from(render_pass.clone(), 0).unwrap())
-            .build(device.clone())
-            .unwrap(),
-    );
-
-    let vertex_uniform_layout = pipeline.layout().descriptor_set_layout(0).unwrap();
-    let mut vertex_uniform_pool = FixedSizeDescriptorSetsPool::new(vertex_uniform_layout.clone());
-
-    let particle_shader =
-        cs_particle_physics::Shader::load(device.clone()).expect("failed to load particle_shader");
-
-    let particle_compute_pipeline = Arc::new(
-        ComputePipeline::new(device.clone(), &particle_shader.main_entry_point(), &())
-            .expect("failed to create particle_compute_pipeline"),
-    );
-
-    let vertex_layout = particle_compute_pipeline
-        .layout()
-        .descriptor_set_layout(0)
-        .unwrap();
-
-    let uniform_layout = particle_compute_pipeline
-        .layout()
-        .descriptor_set_layout(1)
-        .unwrap();
+    vertex_buffer
+}
 
-    let particle_set = Arc::new(
-        PersistentDescriptorSet::start(vertex_layout.clone())
-            .add_buffer(vertex_buffer.clone())
+/// Binds the particles to set 0 of one of the compute shaders.
+pub fn particle_set(
+    pipeline: &Arc<dyn ComputePipelineAbstract + Send + Sync>,
+    particles: Arc<DeviceLocalBuffer<[Vertex]>>,
+) -> Arc<dyn DescriptorSet + Send + Sync> {
+    Arc::new(
+        PersistentDescriptorSet::start(pipeline.layout().descriptor_set_layout(0).unwrap().clone())
+            .add_buffer(particles)
             .unwrap()
             .build()
             .unwrap(),
-    );
-
-    let mut uniform_pool = FixedSizeDescriptorSetsPool::new(uniform_layout.clone());
-
-    let mut previous_frame_end = Some(Box::new(sync::now(device.clone())) as Box<dyn GpuFuture>);
-    let mut recreate_swapchain = false;
-
-    let mut mouse_position: [f32; 2] = [0.0, 0.0];
+    )
+}
 
-    let mut last_time = std::time::Instant::now();
-    let mut delta_time: f32 = 0.0;
-    let mut target_mass: f32 = 1.0;
+impl App for ParticlesApp {
+    fn wants_keyboard(&self) -> bool {
+        self.gui.wants_keyboard()
+    }
 
-    let mut aspect: f32 = 1.0;
+    fn input(&mut self, event: &WindowEvent) {
+        self.gui.input(event);
 
-    events_loop.run(move |event, _, control_flow| {
-        *control_flow = ControlFlow::Poll;
+        // The camera keeps following a drag that started outside of the gui.
         match event {
-            winit::event::Event::WindowEvent { event, .. } => match event {
-                winit::event::WindowEvent::CloseRequested => {
-                    *control_flow = ControlFlow::Exit;
-                }
-                winit::event::WindowEvent::CursorMoved { position, .. } => {
-                    let dimensions = surface.window().inner_size();
-                    mouse_position = [
-                        (((position.x / dimensions.width as f64) - 0.5) * 2.0 / aspect as f64)
-                            as f32,
-                        (((position.y / dimensions.height as f64) - 0.5) * 2.0) as f32,
-                    ];
+            WindowEvent::CursorMoved { position, .. } => {
+                let cursor = [position.x as f32, position.y as f32];
+                if self.panning {
+                    self.camera.pan(self.cursor, cursor, self.dimensions);
                 }
-                winit::event::WindowEvent::MouseWheel { delta, .. } => match delta {
-                    winit::event::MouseScrollDelta::LineDelta(_, dy) => {
-                        target_mass += dy * 0.05;
-                        println!("Mass: {:.2}", target_mass);
-                    }
-                    _ => (),
-                },
-                winit::event::WindowEvent::KeyboardInput {
-                    input:
-                        winit::event::KeyboardInput {
-                            state,
-                            virtual_keycode: Some(kc),
-                            ..
-                        },
-                    ..
-                } => match (kc, state) {
-                    (winit::event::VirtualKeyCode::Escape, winit::event::ElementState::Pressed) => {
-                        *control_flow = ControlFlow::Exit;
-                    }
+                self.attractors.drag(cursor, &self.camera, self.dimensions);
+                self.cursor = cursor;
+                return;
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Released,
+                button,
+                ..
+            } => {
+                match button {
+                    MouseButton::Left => self.attractors.release(),
+                    MouseButton::Right => self.panning = false,
                     _ => (),
-                },
-                _ => (),
-            },
-            winit::event::Event::MainEventsCleared => {
-                let time = std::time::Instant::now();
-                let delta_time_instant = time - last_time;
+                }
+                return;
+            }
+            _ => (),
+        }
 
-                delta_time = delta_time_instant.as_secs_f32();
-                last_time = time;
+        if self.gui.wants_pointer() {
+            return;
+        }
 
-                let uniform_data = ParticleUBO {
-                    target: mouse_position.clone(),
-                    delta_time,
-                    target_mass,
+        match event {
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Right,
+                ..
+            } => self.panning = true,
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } => self.attractors.press(
+                self.cursor,
+                &self.camera,
+                self.dimensions,
+                self.settings.target_mass,
+            ),
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Middle,
+                ..
+            } => {
+                if let Some(index) =
+                    self.attractors
+                        .pick(self.cursor, &self.camera, self.dimensions)
+                {
+                    self.attractors.remove(index);
+                }
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let lines = match delta {
+                    MouseScrollDelta::LineDelta(_, dy) => *dy,
+                    MouseScrollDelta::PixelDelta(p) => p.y as f32 / 24.0,
                 };
-
-                let particle_uniform_buffer = CpuAccessibleBuffer::from_data(
-                    device.clone(),
-                    BufferUsage::all(),
-                    false,
-                    uniform_data,
-                )
-                .expect("failed to create particle_uniform_buffer");
-                let uniform_set = uniform_pool
-                    .next()
-                    .add_buffer(particle_uniform_buffer.clone())
-                    .unwrap()
-                    .build()
-                    .unwrap();
-
-                let particle_cmd_buffer =
-                    AutoCommandBufferBuilder::new(device.clone(), queue.family())
-                        .unwrap()
-                        .dispatch(
-                            [PARTICLE_COUNT / 1024, 1, 1],
-                            particle_compute_pipeline.clone(),
-                            (particle_set.clone(), uniform_set),
-                            (),
-                        )
-                        .unwrap()
-                        .build()
-                        .unwrap();
-
-                let particle_cmd_finished = particle_cmd_buffer.execute(queue.clone()).unwrap();
-                particle_cmd_finished
-                    .then_signal_fence_and_flush()
-                    .unwrap()
-                    .wait(None)
-                    .unwrap();
-
-                surface
-                    .window()
-                    .set_title(format!("FPS: {:.2}", 1.0 / delta_time).as_str());
-
-                surface.window().request_redraw();
+                self.camera
+                    .zoom_at(self.cursor, ZOOM_STEP.powf(lines), self.dimensions);
             }
-            winit::event::Event::RedrawRequested(_) => {
-                previous_frame_end.as_mut().unwrap().cleanup_finished();
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(VirtualKeyCode::R),
+                        ..
+                    },
+                ..
+            } if !self.gui.wants_keyboard() => self.camera.reset(),
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(VirtualKeyCode::Space),
+                        ..
+                    },
+                ..
+            } if !self.gui.wants_keyboard() => self.respawn(),
+            _ => (),
+        }
+    }
 
-                if recreate_swapchain {
-                    let dimensions: [u32; 2] = surface.window().inner_size().into();
+    fn update(&mut self, delta_time: f32) {
+        self.delta_time = delta_time;
+        self.hud.record_frame_time(delta_time);
+        self.attractors
+            .advance(delta_time * self.settings.time_scale);
+
+        let previous = self.settings;
+        let settings = &mut self.settings;
+        let attractors = &mut self.attractors;
+        let mut present_mode = self.present_mode;
+        let particle_count = self.particle_count;
+        let (energy, initial_energy) = (self.energy, self.initial_energy);
+        let mut respawn = false;
+        self.gui.run(self.dimensions, |ctx| {
+            respawn = settings_window(
+                ctx,
+                settings,
+                attractors,
+                &mut present_mode,
+                particle_count,
+                energy,
+                initial_energy,
+            )
+        });
+
+        // Energies under other constants are not comparable.
+        if self.settings.simulation != previous.simulation
+            || self.settings.gravity != previous.gravity
+            || self.settings.softening != previous.softening
+        {
+            self.energy = None;
+            self.initial_energy = None;
+        }
 
-                    aspect = dimensions[1] as f32 / dimensions[0] as f32;
+        if present_mode != self.present_mode {
+            self.requested_present_mode = Some(present_mode);
+        }
+        if respawn {
+            self.respawn();
+        }
+    }
 
-                    println!("New aspect ratio {}", aspect);
+    fn requested_present_mode(&mut self) -> Option<PresentMode> {
+        self.requested_present_mode.take()
+    }
 
-                    let (new_swapchain, new_images) =
-                        match swapchain.recreate_with_dimensions(dimensions) {
-                            Ok(r) => r,
-                            Err(SwapchainCreationError::UnsupportedDimensions) => return,
-                            Err(e) => panic!("Failed to recreate swapchain: {:?}", e),
-                        };
+    fn resize(&mut self, dimensions: [u32; 2]) {
+        self.dimensions = dimensions;
+        self.camera.aspect = dimensions[1] as f32 / dimensions[0] as f32;
 
-                    swapchain = new_swapchain;
-                    framebuffers = window_size_dependent_setup(
-                        &new_images,
-                        render_pass.clone(),
-                        &mut dynamic_state,
-                    );
-                    recreate_swapchain = false;
-                }
-
-                let (image_num, suboptimal, acquire_future) =
-                    match swapchain::acquire_next_image(swapchain.clone(), None) {
-                        Ok(r) => r,
-                        Err(AcquireError::OutOfDate) => {
-                            recreate_swapchain = true;
-                            return;
-                        }
-                        Err(e) => panic!("Failed to acquire next image: {:?}", e),
-                    };
-
-                if suboptimal {
-                    recreate_swapchain = true;
-                }
+        println!("New aspect ratio {}", self.camera.aspect);
+    }
 
-                let vertex_uniform_buffer = CpuAccessibleBuffer::from_data(
-                    device.clone(),
-                    BufferUsage::uniform_buffer(),
-                    false,
-                    VertexUBO { aspect },
-                )
-                .unwrap();
+    fn prepare(
+        &mut self,
+        builder: AutoCommandBufferBuilder,
+        frame: &FrameInfo,
+    ) -> AutoCommandBufferBuilder {
+        self.present_mode = frame.present_mode;
+        let resources = &self.frames[frame.index];
+        let settings = &self.settings;
+        let delta_time = self.delta_time * settings.time_scale;
+
+        let [r, g, b] = settings.slow_color;
+        let slow_color = [r, g, b, 1.0];
+        let [r, g, b] = settings.fast_color;
+        let fast_color = [r, g, b, 1.0];
+        *resources.vertex_uniforms.write().unwrap() = VertexUBO {
+            view: self.camera.view_matrix().into(),
+            slow_color,
+            fast_color,
+        };
+
+        if settings.simulation == Simulation::NBody {
+            // The runner waited for this frame's last submission, its sums are complete.
+            if let Some(energy) = self.nbody.readout(frame.index) {
+                self.energy = Some(energy);
+                self.initial_energy.get_or_insert(energy.total());
+            }
 
-                let vertex_uniform_set = vertex_uniform_pool
-                    .next()
-                    .add_buffer(vertex_uniform_buffer.clone())
-                    .unwrap()
-                    .build()
-                    .unwrap();
+            let params = NBodyParams {
+                gravity: settings.gravity,
+                softening: settings.softening,
+                body_mass: 1.0 / self.particle_count as f32,
+                theta: settings.theta,
+            };
+            return self.nbody.step(
+                builder,
+                frame.index,
+                params,
+                delta_time,
+                settings.solver,
+                settings.measure_energy,
+            );
+        }
 
-                let command_buffer = AutoCommandBufferBuilder::primary_one_time_submit(
-                    device.clone(),
-                    queue.family(),
-                )
-                .unwrap()
-                .begin_render_pass(
-                    framebuffers[image_num].clone(),
-                    false,
-                    vec![[0.0, 0.0, 0.0, 1.0].into()],
-                )
-                .unwrap()
-                .draw(
-                    pipeline.clone(),
-                    &dynamic_state,
-                    vertex_buffer.clone(),
-                    (vertex_uniform_set),
-                    (),
-                )
-                .unwrap()
-                .end_render_pass()
-                .unwrap()
-                .build()
-                .unwrap();
-
-                let future = previous_frame_end
-                    .take()
-                    .unwrap()
-                    .join(acquire_future)
-                    .then_execute(queue.clone(), command_buffer)
-                    .unwrap()
-                    .then_swapchain_present(queue.clone(), swapchain.clone(), image_num)
-                    .then_signal_fence_and_flush();
-
-                match future {
-                    Ok(future) => {
-                        previous_frame_end = Some(Box::new(future) as Box<_>);
-                    }
-                    Err(FlushError::OutOfDate) => {
-                        recreate_swapchain = true;
-                        previous_frame_end = Some(Box::new(sync::now(device.clone())) as Box<_>);
-                    }
-                    Err(e) => {
-                        println!("Failed to flush future {:?}", e);
-                        previous_frame_end = Some(Box::new(sync::now(device.clone())) as Box<_>);
-                    }
-                }
-            }
-            _ => (),
+        let mut attractors = resources.attractors.write().unwrap();
+        let mut attractor_count = self.attractors.write_gpu(&mut attractors);
+        if settings.follow_cursor && attractor_count < MAX_ATTRACTORS {
+            // Converted every frame, the view can move under a resting cursor.
+            let target = self.camera.screen_to_world(self.cursor, self.dimensions);
+            attractors[attractor_count] = Attractor::new(target, settings.target_mass).gpu(0.0);
+            attractor_count += 1;
         }
-    });
+        drop(attractors);
+
+        *resources.particle_uniforms.write().unwrap() = ParticleUBO {
+            delta_time,
+            damping: settings.damping,
+            attractor_count: attractor_count as u32,
+        };
+
+        builder
+            .dispatch(
+                // The last workgroup may run past the end, the shader skips those invocations.
+                [
+                    (self.particle_count + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE,
+                    1,
+                    1,
+                ],
+                self.particle_compute_pipeline.clone(),
+                (
+                    self.particle_set.clone(),
+                    resources.particle_uniform_set.clone(),
+                ),
+                (),
+            )
+            .unwrap()
+    }
+
+    fn record(
+        &mut self,
+        builder: AutoCommandBufferBuilder,
+        dynamic_state: &DynamicState,
+        frame: &FrameInfo,
+    ) -> AutoCommandBufferBuilder {
+        let builder = builder
+            .draw(
+                self.pipeline.clone(),
+                dynamic_state,
+                self.vertex_buffer.clone(),
+                self.frames[frame.index].vertex_uniform_set.clone(),
+                (),
+            )
+            .unwrap();
+
+        self.hud.text(
+            [8.0, 8.0],
+            2.0,
+            [1.0, 1.0, 1.0, 1.0],
+            &format!(
+                "FPS: {:.1} ({:.2} ms)\n\
+                 Particles: {}\n\
+                 Target mass: {:.2}  Zoom: {:.1}x\n\
+                 Present mode: {:?}\n\
+                 Frames in flight: {}\n\
+                 V: vsync  F12: screenshot  R: reset view  Space: respawn",
+                1.0 / self.delta_time,
+                self.delta_time * 1000.0,
+                self.particle_count,
+                self.settings.target_mass,
+                self.camera.zoom,
+                frame.present_mode,
+                self.frames_in_flight
+            ),
+        );
+        self.hud
+            .frame_time_graph([8.0, 136.0], [240.0, 60.0], 1.0 / 30.0);
+        self.attractors
+            .draw(&mut self.hud, &self.camera, frame.dimensions);
+
+        let builder = self.hud.draw(
+            builder,
+            dynamic_state,
+            [frame.dimensions[0] as f32, frame.dimensions[1] as f32],
+        );
+
+        self.gui.draw(builder, dynamic_state, frame.dimensions)
+    }
 }
 
-fn window_size_dependent_setup(
-    images: &[Arc<SwapchainImage<Window>>],
-    render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
-    dynamic_state: &mut DynamicState,
-) -> Vec<Arc<dyn FramebufferAbstract + Send + Sync>> {
-    let dimensions = images[0].dimensions();
-
-    let viewport = Viewport {
-        origin: [0.0, 0.0],
-        dimensions: [dimensions[0] as f32, dimensions[1] as f32],
-        depth_range: 0.0..1.0,
-    };
-
-    dynamic_state.viewports = Some(vec![viewport]);
-
-    images
-        .iter()
-        .map(|image| {
-            Arc::new(
-                Framebuffer::start(render_pass.clone())
-                    .add(image.clone())
-                    .unwrap()
-                    .build()
-                    .unwrap(),
-            ) as Arc<dyn FramebufferAbstract + Send + Sync>
-        })
-        .collect::<Vec<_>>()
+pub fn graphics_window(
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    instance: Arc<Instance>,
+    samples: u32,
+    present_mode: PresentMode,
+    frames_in_flight: usize,
+    options: ParticleOptions,
+) {
+    run_app(
+        device,
+        queue,
+        instance,
+        AppOptions {
+            title: "Particles".to_string(),
+            samples,
+            present_mode,
+            frames_in_flight,
+            depth: false,
+        },
+        move |context| ParticlesApp::new(context, options),
+    );
 }
 
 #[derive(Default, Copy, Clone)]
 pub struct Vertex {
-    position: [f32; 2],
-    velocity: [f32; 2],
+    pub position: [f32; 2],
+    pub velocity: [f32; 2],
 }
+vulkano::impl_vertex!(Vertex, position, velocity);
 
-impl Vertex {
-    fn new(x: f32, y: f32) -> Vertex {
-        Vertex {
-            position: [x, y],
-            velocity: [0.0, 0.0],
-        }
-    }
+/// What one frame in flight records with. Its buffers are rewritten once the runner has waited
+/// for the submission that last read them.
+struct Frame {
+    particle_uniforms: Arc<CpuAccessibleBuffer<ParticleUBO>>,
+    attractors: Arc<CpuAccessibleBuffer<[GpuAttractor]>>,
+    particle_uniform_set: Arc<dyn DescriptorSet + Send + Sync>,
+    vertex_uniforms: Arc<CpuAccessibleBuffer<VertexUBO>>,
+    vertex_uniform_set: Arc<dyn DescriptorSet + Send + Sync>,
 }
-vulkano::impl_vertex!(Vertex, position, velocity);
 
+#[repr(C)]
 struct ParticleUBO {
-    target: [f32; 2],
     delta_time: f32,
-    target_mass: f32,
+    damping: f32,
+    attractor_count: u32,
 }
 
+#[repr(C)]
 struct VertexUBO {
-    aspect: f32,
+    view: [[f32; 4]; 4],
+    slow_color: [f32; 4],
+    fast_color: [f32; 4],
 }
 
 mod vs_graphics {
@@ -459,7 +860,14 @@ mod vs_graphics {
 mod fs_graphics {
     vulkano_shaders::shader! {
         ty: "fragment",
-        path: "shaders/graphics.frag.glsl"
+        path: "shaders/particles.frag.glsl"
+    }
+}
+
+mod cs_particle_init {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "shaders/particle_init.comp.glsl"
     }
 }
 
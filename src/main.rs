@@ -4,28 +4,270 @@ use std::sync::Arc;
 
 mod examples;
 use crate::examples::{
-    compute_mandel_and_save, compute_shader_multiply, copy_buffers, graphics_pipeline,
-    graphics_window, image_clear_and_save, vulkano_particles,
+    barnes_hut_check, compute_mandel_and_save, compute_shader_multiply, copy_buffers, edge_filters,
+    format_matrix_check, graphics_pipeline, graphics_window, image_blit_and_save,
+    image_clear_and_save, image_filter_and_save, image_filter_check, instanced_quads_and_save,
+    instanced_quads_window, mandel_explorer, max_particle_count, mesh_offscreen_and_save,
+    mesh_window, mip_chain_check, parse_present_mode, scene_offscreen_and_save, scene_window,
+    textured_quad_and_save, textured_quad_window, vulkano_particles, Distribution,
+    DownsampleFilter, ImageOutput, ParticleOptions, SamplePattern, SamplerOptions, Supersampling,
 };
 use vulkano::device::{Device, DeviceExtensions, Features, Queue};
 use vulkano::instance::{Instance, PhysicalDevice};
 
 fn main() {
-    let (device, queue, instance) = init_vulkan();
+    let mut args = Vec::new();
+    let mut samples = 1;
+    let mut filter = "linear".to_string();
+    let mut address_mode = "repeat".to_string();
+    let mut repeat = 1.0;
+    let mut present_mode = "fifo".to_string();
+    let mut frames_in_flight = 2;
+    let mut particle_count = 1_048_576;
+    let mut seed = 0;
+    let mut distribution = "square".to_string();
+    let mut input = None;
+
+    let mut raw_args = std::env::args().skip(1);
+    while let Some(arg) = raw_args.next() {
+        match arg.as_str() {
+            "--samples" => {
+                samples = raw_args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .expect("--samples expects a sample count");
+            }
+            "--filter" => filter = raw_args.next().expect("--filter expects nearest or linear"),
+            "--address" => {
+                address_mode = raw_args
+                    .next()
+                    .expect("--address expects repeat, mirror, clamp or border");
+            }
+            "--repeat" => {
+                repeat = raw_args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .expect("--repeat expects a number");
+            }
+            "--present" => {
+                present_mode = raw_args
+                    .next()
+                    .expect("--present expects fifo, relaxed, mailbox or immediate");
+            }
+            "--frames" => {
+                frames_in_flight = raw_args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .expect("--frames expects a number of frames in flight");
+            }
+            "--particles" => {
+                particle_count = raw_args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .filter(|&n| n > 0)
+                    .expect("--particles expects a positive particle count");
+            }
+            "--seed" => {
+                seed = raw_args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .expect("--seed expects a number");
+            }
+            "--distribution" => {
+                distribution = raw_args.next().expect(
+                    "--distribution expects square, disc, clusters, galaxy, ring, spiral or grid",
+                );
+            }
+            "--input" => input = Some(raw_args.next().expect("--input expects an image path")),
+            _ => args.push(arg),
+        }
+    }
+
+    let example = args.get(0).map(|a| a.as_str()).unwrap_or("particles");
+    let output = |default: &str| args.get(1).cloned().unwrap_or_else(|| default.to_string());
 
-    // copy_buffers(device.clone(), queue.clone());
-    //
-    // compute_shader_multiply(device.clone(), queue.clone());
-    //
-    // image_clear_and_save(device.clone(), queue.clone());
-    //
-    // compute_mandel_and_save(device.clone(), queue.clone());
-    //
-    // graphics_pipeline(device.clone(), queue.clone());
+    let sampler_options = SamplerOptions::parse(&filter, &address_mode, repeat)
+        .expect("unknown --filter or --address");
+
+    let present_mode = parse_present_mode(&present_mode).expect("unknown --present");
+    let distribution = Distribution::parse(&distribution).expect("unknown --distribution");
+
+    let (device, queue, instance) = init_vulkan();
 
-    // graphics_window(device.clone(), queue.clone(), instance.clone());
+    match example {
+        "copy" => copy_buffers(device.clone(), queue.clone()),
+        "multiply" => compute_shader_multiply(device.clone(), queue.clone()),
+        "clear" => {
+            let path = output("image.png");
+            image_clear_and_save(
+                device.clone(),
+                queue.clone(),
+                ImageOutput::for_path(&path),
+                path,
+            );
+        }
+        "formats" => format_matrix_check(device.clone(), queue.clone()),
+        "barnes-hut" => barnes_hut_check(device.clone(), queue.clone()),
+        "mandel" => {
+            let path = output("mandel.png");
+            compute_mandel_and_save(
+                device.clone(),
+                queue.clone(),
+                Supersampling::none(),
+                ImageOutput::for_path(&path),
+                path,
+            );
+        }
+        "mandel-aa" => {
+            let path = output("mandel_aa.png");
+            compute_mandel_and_save(
+                device.clone(),
+                queue.clone(),
+                Supersampling {
+                    grid_size: 4,
+                    pattern: SamplePattern::RotatedGrid,
+                    filter: DownsampleFilter::Lanczos,
+                    adaptive_threshold: Some(0.02),
+                },
+                ImageOutput::for_path(&path),
+                path,
+            );
+        }
+        "triangle" => graphics_pipeline(
+            device.clone(),
+            queue.clone(),
+            samples,
+            output("triangle.png"),
+        ),
+        "blit" => image_blit_and_save(device.clone(), queue.clone(), &output("checkerboard")),
+        "blit-check" => mip_chain_check(device.clone(), queue.clone()),
+        "filter" => image_filter_and_save(
+            device.clone(),
+            queue.clone(),
+            input.as_deref().unwrap_or("mandel.png"),
+            &edge_filters(),
+            output("mandel_edges.png"),
+        ),
+        "filter-check" => image_filter_check(device.clone(), queue.clone(), &edge_filters()),
+        "mesh" => mesh_offscreen_and_save(
+            device.clone(),
+            queue.clone(),
+            args.get(2).map(|a| a.as_str()).unwrap_or("assets/cube.obj"),
+            samples,
+            output("mesh.png"),
+        ),
+        "mesh-window" => mesh_window(
+            device.clone(),
+            queue.clone(),
+            instance.clone(),
+            args.get(1).map(|a| a.as_str()).unwrap_or("assets/cube.obj"),
+            samples,
+            present_mode,
+        ),
+        "scene" => scene_offscreen_and_save(
+            device.clone(),
+            queue.clone(),
+            args.get(2)
+                .map(|a| a.as_str())
+                .unwrap_or("assets/textured_cube.gltf"),
+            samples,
+            output("scene.png"),
+        ),
+        "scene-window" => scene_window(
+            device.clone(),
+            queue.clone(),
+            instance.clone(),
+            args.get(1)
+                .map(|a| a.as_str())
+                .unwrap_or("assets/textured_cube.gltf"),
+            samples,
+            present_mode,
+        ),
+        "texture" => textured_quad_and_save(
+            device.clone(),
+            queue.clone(),
+            args.get(2).map(|a| a.as_str()).unwrap_or("mandel.png"),
+            sampler_options,
+            samples,
+            output("textured.png"),
+        ),
+        "texture-window" => textured_quad_window(
+            device.clone(),
+            queue.clone(),
+            instance.clone(),
+            args.get(1).map(|a| a.as_str()).unwrap_or("mandel.png"),
+            sampler_options,
+            samples,
+            present_mode,
+        ),
+        "instances" => instanced_quads_and_save(
+            device.clone(),
+            queue.clone(),
+            args.get(2)
+                .map(|n| n.parse().expect("expected an instance count"))
+                .unwrap_or(10_000),
+            samples,
+            output("instances.png"),
+        ),
+        "instances-window" => instanced_quads_window(
+            device.clone(),
+            queue.clone(),
+            instance.clone(),
+            args.get(1)
+                .map(|n| n.parse().expect("expected an instance count"))
+                .unwrap_or(10_000),
+            samples,
+            present_mode,
+        ),
+        "window" => graphics_window(
+            device.clone(),
+            queue.clone(),
+            instance.clone(),
+            samples,
+            present_mode,
+        ),
+        "mandel-window" => mandel_explorer(
+            device.clone(),
+            queue.clone(),
+            instance.clone(),
+            samples,
+            present_mode,
+        ),
+        "particles" => {
+            let max_particles = max_particle_count(device.physical_device());
+            if particle_count > max_particles {
+                eprintln!(
+                    "--particles {} is too many, this device takes {} at most",
+                    particle_count, max_particles
+                );
+                std::process::exit(2);
+            }
 
-    vulkano_particles(device.clone(), queue.clone(), instance.clone());
+            vulkano_particles(
+                device.clone(),
+                queue.clone(),
+                instance.clone(),
+                samples,
+                present_mode,
+                frames_in_flight,
+                ParticleOptions {
+                    particle_count,
+                    seed,
+                    distribution,
+                },
+            );
+        }
+        _ => {
+            eprintln!(
+                "unknown example {}, expected one of copy, multiply, clear, formats, barnes-hut, \
+                 mandel, mandel-aa, mandel-window, triangle, blit, blit-check, filter, \
+                 filter-check, mesh, mesh-window, scene, scene-window, texture, texture-window, \
+                 instances, instances-window, window or particles",
+                example
+            );
+            std::process::exit(2);
+        }
+    }
 }
 
 fn init_vulkan() -> (Arc<Device>, Arc<Queue>, Arc<Instance>) {
@@ -52,6 +294,10 @@ fn init_vulkan() -> (Arc<Device>, Arc<Queue>, Arc<Instance>) {
             physical_dev,
             &Features {
                 fill_mode_non_solid: true,
+                // `rgba16` storage images, which 16 bit PNGs are rendered into.
+                shader_storage_image_extended_formats: physical_dev
+                    .supported_features()
+                    .shader_storage_image_extended_formats,
                 ..Features::none()
             },
             &DeviceExtensions {
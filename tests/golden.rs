@@ -0,0 +1,174 @@
+//! Renders the offscreen examples and compares them against the images checked in at the root
+//! of the repository, and runs the examples that check themselves.
+//!
+//! The examples need a Vulkan device. On machines without a GPU, install a software driver such
+//! as lavapipe (`mesa-vulkan-drivers` on Debian and Ubuntu) and point `VK_ICD_FILENAMES` at its
+//! ICD manifest, e.g. `/usr/share/vulkan/icd.d/lvp_icd.x86_64.json`. When no device can be found
+//! at all, every test prints that it was skipped and passes, unless `GOLDEN_REQUIRE_DEVICE` is
+//! set. Set it in CI next to `VK_ICD_FILENAMES`, so a broken driver setup fails the suite
+//! instead of skipping it.
+//!
+//! When an image differs, a diff image is written next to the rendered output in
+//! `target/golden`, with mismatching pixels in red.
+
+use image::{Rgba, RgbaImage};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// How much a rendered image may differ from its reference.
+struct Tolerance {
+    /// Largest difference of a single channel that still counts as matching.
+    channel: u8,
+    /// Fraction of pixels allowed to exceed `channel`.
+    pixels: f64,
+}
+
+fn output_dir() -> PathBuf {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("target")
+        .join("golden");
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Runs `example` with `args` and panics if it fails. Returns false if there is no Vulkan
+/// device to run it on, see the top of this file.
+fn run(example: &str, args: &[&Path]) -> bool {
+    let result = Command::new(env!("CARGO_BIN_EXE_vulkan-particles"))
+        .arg(example)
+        .args(args)
+        .output()
+        .expect("failed to run example");
+
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        let no_device =
+            stderr.contains("failed to create instance") || stderr.contains("no device available");
+
+        if no_device && std::env::var_os("GOLDEN_REQUIRE_DEVICE").is_none() {
+            eprintln!(
+                "skipping {}: no Vulkan device, see the top of tests/golden.rs",
+                example
+            );
+            return false;
+        }
+        panic!("{} failed:\n{}", example, stderr);
+    }
+    true
+}
+
+fn assert_matches_reference(example: &str, reference: &str, tolerance: Tolerance) {
+    let output = output_dir().join(reference);
+    if !run(example, &[output.as_path()]) {
+        return;
+    }
+
+    let expected = image::open(Path::new(env!("CARGO_MANIFEST_DIR")).join(reference))
+        .expect("failed to load reference")
+        .to_rgba8();
+    let actual = image::open(&output)
+        .expect("failed to load rendered image")
+        .to_rgba8();
+
+    assert_eq!(
+        expected.dimensions(),
+        actual.dimensions(),
+        "{} has the wrong size",
+        example
+    );
+
+    let (width, height) = expected.dimensions();
+    let mut diff = RgbaImage::new(width, height);
+    let mut mismatches = 0;
+
+    for (x, y, expected_pixel) in expected.enumerate_pixels() {
+        let actual_pixel = actual.get_pixel(x, y);
+        let difference = expected_pixel
+            .0
+            .iter()
+            .zip(actual_pixel.0.iter())
+            .map(|(a, b)| (*a as i16 - *b as i16).abs() as u8)
+            .max()
+            .unwrap();
+
+        if difference > tolerance.channel {
+            mismatches += 1;
+            diff.put_pixel(x, y, Rgba([255, 0, 0, 255]));
+        } else {
+            // Keep a faint copy of the reference so mismatches can be located.
+            let grey = (expected_pixel.0[..3].iter().map(|&c| c as u32).sum::<u32>() / 12) as u8;
+            diff.put_pixel(x, y, Rgba([grey, grey, grey, 255]));
+        }
+    }
+
+    let allowed = (tolerance.pixels * (width * height) as f64) as usize;
+    if mismatches > allowed {
+        let diff_path = output.with_file_name(format!("{}_diff.png", example));
+        diff.save(&diff_path).unwrap();
+
+        panic!(
+            "{} differs from {} in {} pixels (allowed {}), see {}",
+            example,
+            reference,
+            mismatches,
+            allowed,
+            diff_path.display()
+        );
+    }
+}
+
+#[test]
+fn triangle_matches_reference() {
+    assert_matches_reference(
+        "triangle",
+        "triangle.png",
+        Tolerance {
+            channel: 1,
+            pixels: 0.001,
+        },
+    );
+}
+
+#[test]
+fn mandelbrot_matches_reference() {
+    // Escape times near the boundary are sensitive to float precision across drivers.
+    assert_matches_reference(
+        "mandel",
+        "mandel.png",
+        Tolerance {
+            channel: 2,
+            pixels: 0.01,
+        },
+    );
+}
+
+#[test]
+fn clear_matches_reference() {
+    assert_matches_reference(
+        "clear",
+        "image.png",
+        Tolerance {
+            channel: 0,
+            pixels: 0.0,
+        },
+    );
+}
+
+#[test]
+fn mip_levels_are_halved_checkerboards() {
+    // Reads back every level of a blitted mip chain, with the expectations in `mip_chain_check`.
+    run("blit-check", &[]);
+}
+
+#[test]
+fn filter_chain_matches_cpu_reference() {
+    // Runs blur, Sobel and threshold over a small generated image and compares the result to
+    // `filter_on_cpu`, with the tolerances in image_filter.rs.
+    run("filter-check", &[]);
+}
+
+#[test]
+fn barnes_hut_matches_direct_sum() {
+    // The check asserts itself, with the tolerances in `barnes_hut_check`.
+    run("barnes-hut", &[]);
+}